@@ -8,22 +8,35 @@ use syn::{
 
 #[proc_macro_attribute]
 pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let attr = proc_macro2::TokenStream::from(attr);
-    if !attr.is_empty() {
-        return Error::new(Span::call_site(), "#[tool] does not accept any arguments")
-            .into_compile_error()
-            .into();
-    }
+    let strict = match parse_strict(proc_macro2::TokenStream::from(attr)) {
+        Ok(strict) => strict,
+        Err(err) => return err.into_compile_error().into(),
+    };
 
     let item_fn = parse_macro_input!(item as ItemFn);
 
-    match expand_tool(item_fn) {
+    match expand_tool(item_fn, strict) {
         Ok(tokens) => tokens.into(),
         Err(err) => err.into_compile_error().into(),
     }
 }
 
-fn expand_tool(item_fn: ItemFn) -> Result<proc_macro2::TokenStream> {
+/// Parses the arguments of `#[tool]`, which may only be `strict`.
+fn parse_strict(attr: proc_macro2::TokenStream) -> Result<bool> {
+    if attr.is_empty() {
+        return Ok(false);
+    }
+
+    match syn::parse2::<Ident>(attr.clone()) {
+        Ok(ident) if ident == "strict" => Ok(true),
+        _ => Err(Error::new_spanned(
+            attr,
+            "#[tool] only accepts `strict` as an argument",
+        )),
+    }
+}
+
+fn expand_tool(item_fn: ItemFn, strict: bool) -> Result<proc_macro2::TokenStream> {
     if item_fn.sig.asyncness.is_none() {
         return Err(Error::new_spanned(
             &item_fn.sig.ident,
@@ -55,6 +68,15 @@ fn expand_tool(item_fn: ItemFn) -> Result<proc_macro2::TokenStream> {
         .collect::<Vec<_>>();
 
     let params = parse_params(&item_fn)?;
+    if strict {
+        // Strict mode requires every property, so optional parameters cannot be expressed.
+        if let Some((_, ty)) = params.iter().find(|(_, ty)| is_option(ty)) {
+            return Err(Error::new_spanned(
+                ty,
+                "#[tool(strict)] does not support optional parameters",
+            ));
+        }
+    }
     let arg_fields = params
         .iter()
         .map(|(ident, ty)| quote! { #ident: #ty })
@@ -83,10 +105,22 @@ fn expand_tool(item_fn: ItemFn) -> Result<proc_macro2::TokenStream> {
 
         #(#cfg_attrs)*
         #[doc(hidden)]
-        fn #schema_fn_ident() -> &'static ::deepseek_api::__private::schemars::Schema {
-            static SCHEMA: ::std::sync::OnceLock<::deepseek_api::__private::schemars::Schema> =
-                ::std::sync::OnceLock::new();
-            SCHEMA.get_or_init(|| ::deepseek_api::__private::schemars::schema_for!(#args_struct_ident))
+        fn #schema_fn_ident() -> &'static ::std::result::Result<
+            ::deepseek_api::__private::schemars::Schema,
+            ::std::string::String,
+        > {
+            static SCHEMA: ::std::sync::OnceLock<
+                ::std::result::Result<
+                    ::deepseek_api::__private::schemars::Schema,
+                    ::std::string::String,
+                >,
+            > = ::std::sync::OnceLock::new();
+            SCHEMA.get_or_init(|| {
+                ::deepseek_api::__private::parameters_schema(
+                    ::deepseek_api::__private::schemars::schema_for!(#args_struct_ident),
+                    #strict,
+                )
+            })
         }
 
         #(#cfg_attrs)*
@@ -94,9 +128,9 @@ fn expand_tool(item_fn: ItemFn) -> Result<proc_macro2::TokenStream> {
         fn #call_fn_ident(args: ::std::string::String) -> ::deepseek_api::ToolFuture {
             ::std::boxed::Box::pin(async move {
                 let __deepseek_api_args: #args_struct_ident =
-                    ::deepseek_api::__private::serde_json::from_str(&args).unwrap();
+                    ::deepseek_api::__private::parse_arguments(&args)?;
                 let result = #invoke;
-                ::deepseek_api::__private::serde_json::to_string(&result).unwrap()
+                ::std::result::Result::Ok(::deepseek_api::__private::serialize_result(&result))
             })
         }
 
@@ -106,6 +140,7 @@ fn expand_tool(item_fn: ItemFn) -> Result<proc_macro2::TokenStream> {
             #description,
             #schema_fn_ident,
             #call_fn_ident,
+            #strict,
         );
     })
 }
@@ -133,6 +168,20 @@ fn parse_params(item_fn: &ItemFn) -> Result<Vec<(Ident, syn::Type)>> {
         .collect()
 }
 
+/// Whether `ty` is spelled as an `Option`, e.g. `Option<u32>` or `std::option::Option<u32>`.
+fn is_option(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        syn::Type::Group(group) => is_option(&group.elem),
+        syn::Type::Paren(paren) => is_option(&paren.elem),
+        _ => false,
+    }
+}
+
 fn extract_description(attrs: &[Attribute]) -> Result<String> {
     let mut lines = Vec::new();
 
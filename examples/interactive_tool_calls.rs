@@ -1,8 +1,8 @@
 use std::io::Write;
 
 use colored::Colorize;
-use deepseek_api::AsyncIteratorNext;
-use deepseek_api::{Client, Delta, Model, tool};
+use deepseek_api::{ChatOptions, Client, Delta, Model, tool};
+use futures_util::StreamExt;
 
 #[tool]
 /// Adds two integers.
@@ -35,9 +35,9 @@ impl State {
 
         if *self != mode {
             match self {
-                UserInput => println!(""),
+                UserInput => println!(),
                 ToolCallInput => println!("{}", ")".yellow()),
-                ToolCallOutput => println!(""),
+                ToolCallOutput => println!(),
                 _ => println!("\n"),
             };
             *self = mode
@@ -61,9 +61,10 @@ async fn main() {
     loop {
         mode.transition_to(State::UserInput);
         let prompt = input();
-        let mut stream = client.streaming_chat(&prompt).await;
+        let mut stream = client.chat_stream(&prompt, ChatOptions::default());
 
         while let Some(delta) = stream.next().await {
+            let delta = delta.unwrap();
             use Delta::*;
             match delta {
                 Thinking {
@@ -105,6 +106,7 @@ async fn main() {
                     mode.transition_to(State::ToolCallOutput);
                     println!("{}{} = {content}", "@".blue(), tool_call_id.blue());
                 }
+                Usage(_) | Finished(_) => {}
             }
             std::io::stdout().flush().unwrap();
         }
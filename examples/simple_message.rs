@@ -1,18 +1,17 @@
-use deepseek_api::{Client, Model, message::Message};
+use deepseek_api::{Client, Model};
 
 #[tokio::main]
 async fn main() {
     let api_key = std::env::var("DEEPSEEK_API_KEY").unwrap();
     let mut client = Client::new(Model::DeepSeekChat, &api_key);
-    let answers = client.chat("Hello!").await;
+    let response = client.chat("Hello!").await.unwrap();
 
     // It must be 1 since there is no tool calls.
-    assert_eq!(answers.len(), 1);
+    assert_eq!(response.messages.len(), 1);
 
-    let answer = match &answers[0] {
-        Message::Assistant(assistant) => &assistant.content,
-        _ => unreachable!(),
-    };
-
-    println!("{answer}");
+    println!("{}", response.text().unwrap());
+    println!(
+        "({} tokens, request {})",
+        response.usage.total_tokens, response.round_trips[0].id
+    );
 }
@@ -1,17 +1,18 @@
 use std::io::Write;
 
 use colored::Colorize;
-use deepseek_api::AsyncIteratorNext;
-use deepseek_api::{Client, Delta, Model};
+use deepseek_api::{ChatOptions, Client, Delta, Model};
+use futures_util::StreamExt;
 
 #[tokio::main]
 async fn main() {
     let api_key = std::env::var("DEEPSEEK_API_KEY").unwrap();
     let mut client = Client::new(Model::DeepSeekReasoner, &api_key);
-    let mut stream = client.streaming_chat("Hello!").await;
+    let mut stream = client.chat_stream("Hello!", ChatOptions::default());
 
     let mut is_thinking = true;
     while let Some(delta) = stream.next().await {
+        let delta = delta.unwrap();
         match delta {
             Delta::Thinking {
                 reasoning_content, ..
@@ -27,6 +28,8 @@ async fn main() {
                 print!("{content}");
                 std::io::stdout().flush().unwrap();
             }
+            Delta::Usage(usage) => println!("\n\n({} tokens)", usage.total_tokens),
+            Delta::Finished(_) => {}
             _ => unreachable!(),
         }
     }
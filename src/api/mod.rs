@@ -5,6 +5,6 @@ pub mod response;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
-pub(crate) enum ToolCallType {
+pub enum ToolCallType {
     Function,
 }
@@ -9,6 +9,10 @@ pub struct ChatCompletionRequest {
     pub model: Model,
     pub stream: bool,
 
+    /// Options for the streaming response. Only set this when `stream` is `true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+
     /// Number between -2.0 and 2.0. Positive values penalize new tokens based on their existing frequency in the text so far, decreasing the model's likelihood to repeat the same line verbatim.
     pub frequency_penalty: Option<f32>,
 
@@ -27,6 +31,10 @@ pub struct ChatCompletionRequest {
     /// Important: When using JSON Output, you must also instruct the model to produce JSON yourself via a system or user message. Without this, the model may generate an unending stream of whitespace until the generation reaches the token limit, resulting in a long-running and seemingly "stuck" request. Also note that the message content may be partially cut off if finish_reason="length", which indicates the generation exceeded max_tokens or the conversation exceeded the max context length.
     pub response_format: ResponseFormat,
 
+    /// Up to 16 sequences where the API will stop generating further tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+
     /// What sampling temperature to use, between 0 and 2. Higher values like 0.8 will make the output more random, while lower values like 0.2 will make it more focused and deterministic.
     ///
     /// We generally recommend altering this or `top_p` but not both.
@@ -39,6 +47,53 @@ pub struct ChatCompletionRequest {
 
     /// A list of tools the model may call. Currently, only functions are supported as a tool. Use this to provide a list of functions the model may generate JSON inputs for. A max of 128 functions are supported.
     pub tools: Vec<Tool>,
+
+    /// Controls which (if any) tool is called by the model. Only allowed when `tools` is not empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+}
+
+/// A request to the beta FIM completion endpoint.
+#[derive(Serialize)]
+pub struct CompletionRequest {
+    pub model: Model,
+    pub prompt: String,
+
+    /// The text after the completion.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+
+    /// Echo back the prompt in addition to the completion.
+    pub echo: bool,
+
+    pub frequency_penalty: f32,
+
+    /// Include the log probabilities of this many most likely tokens, at most 20.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+
+    pub presence_penalty: f32,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+
+    pub stream: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+
+    pub temperature: f32,
+
+    pub top_p: f32,
+}
+
+#[derive(Serialize)]
+pub struct StreamOptions {
+    /// If set, an additional chunk carrying the token usage of the entire request is streamed before `data: [DONE]`.
+    pub include_usage: bool,
 }
 
 #[derive(Serialize)]
@@ -57,6 +112,10 @@ pub enum Message {
         content: String,
         reasoning_content: Option<String>,
         tool_calls: Option<Vec<ToolCall>>,
+        /// Makes the model continue this message instead of starting a new one. Only allowed on
+        /// the last message, and only by the beta endpoint.
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        prefix: bool,
     },
     Tool {
         tool_call_id: String,
@@ -85,9 +144,35 @@ pub enum Tool {
         name: &'static str,
         description: &'static str,
         parameters: &'static Schema,
+        /// Makes the model adhere to `parameters`. Only supported by the beta endpoint.
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        strict: bool,
     },
 }
 
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum ToolChoice {
+    Mode(ToolChoiceMode),
+    Function {
+        r#type: ToolCallType,
+        function: FunctionName,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoiceMode {
+    None,
+    Auto,
+    Required,
+}
+
+#[derive(Serialize)]
+pub struct FunctionName {
+    pub name: String,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ResponseFormat {
     r#type: ResponseFormatType,
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Model,
+    api::response::fields::{FinishReason, Usage},
+};
+
+/// A response of the FIM completion endpoint, or a chunk of its streamed response.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Completion {
+    pub id: String,
+    pub choices: Vec<Choice>,
+    pub created: u64,
+    pub model: Model,
+    pub system_fingerprint: String,
+    pub object: Object,
+    /// Always present in a non-streamed response. When streaming, only present in the last
+    /// chunk.
+    pub usage: Option<Usage>,
+}
+
+impl Completion {
+    /// The generated text of the first choice.
+    pub fn text(&self) -> &str {
+        self.choices.first().map_or("", |choice| &choice.text)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Choice {
+    pub index: u64,
+    /// The generated text, preceded by the prompt if `echo` was set.
+    pub text: String,
+    /// Only present in the last chunk when streaming.
+    pub finish_reason: Option<FinishReason>,
+    /// Present if `logprobs` was requested.
+    pub logprobs: Option<Logprobs>,
+}
+
+/// The log probabilities of the generated tokens.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Logprobs {
+    /// The offset of each token in the text.
+    #[serde(default)]
+    pub text_offset: Vec<u32>,
+    #[serde(default)]
+    pub token_logprobs: Vec<f64>,
+    #[serde(default)]
+    pub tokens: Vec<String>,
+    /// The most likely tokens at each position, with their log probabilities.
+    #[serde(default)]
+    pub top_logprobs: Vec<HashMap<String, f64>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum Object {
+    #[serde(rename = "text_completion")]
+    TextCompletion,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streamed_chunk_is_parsed() {
+        let chunk = r#"{"id":"b5f6c4c2","object":"text_completion","created":1738000000,"model":"deepseek-chat","system_fingerprint":"fp_3a5770e1b4","choices":[{"text":"    return a + b","index":0,"logprobs":{"text_offset":[0],"token_logprobs":[-0.01],"tokens":["    return a + b"],"top_logprobs":[{"    return a + b":-0.01}]},"finish_reason":null}]}"#;
+        let chunk: Completion = serde_json::from_str(chunk).unwrap();
+
+        assert_eq!(chunk.text(), "    return a + b");
+        assert!(chunk.usage.is_none());
+        let choice = &chunk.choices[0];
+        assert!(choice.finish_reason.is_none());
+        assert_eq!(choice.logprobs.as_ref().unwrap().token_logprobs, [-0.01]);
+    }
+}
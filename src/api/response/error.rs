@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// The body of a non-success response, e.g. `{"error": {"message": ..., "type": ..., "code": ...}}`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ErrorResponse {
+    pub error: ErrorDetail,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ErrorDetail {
+    /// A human-readable description of the error.
+    pub message: String,
+    /// The error category reported by the API, e.g. `invalid_request_error`.
+    pub r#type: Option<String>,
+    /// A machine-readable error code, if any.
+    pub code: Option<String>,
+}
@@ -1,30 +1,70 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Why the model stopped generating.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FinishReason {
+    /// The answer is complete, or a stop sequence was hit.
     #[serde(rename = "stop")]
     Stop,
+    /// The answer was cut off by `max_tokens` or the context length, see
+    /// [`Client::max_continuations`](crate::Client::max_continuations).
     #[serde(rename = "length")]
     Length,
+    /// The answer was cut off by the content filter.
     #[serde(rename = "content_filter")]
     ContentFilter,
+    /// The model called tools.
     #[serde(rename = "tool_calls")]
     ToolCalls,
+    /// The answer was cut off because the inference system ran out of resources.
     #[serde(rename = "insufficient_system_resource")]
     InsufficientSystemResource,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Token usage of a completion request.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Usage {
-    completion_tokens: u64,
-    prompt_tokens: u64,
-    prompt_cache_hit_tokens: u64,
-    prompt_cache_miss_tokens: u64,
-    total_tokens: u64,
-    completion_tokens_details: Option<CompletionTokensDetails>,
+    /// Number of tokens in the generated completion.
+    pub completion_tokens: u64,
+    /// Number of tokens in the prompt. It equals `prompt_cache_hit_tokens + prompt_cache_miss_tokens`.
+    pub prompt_tokens: u64,
+    /// Number of tokens in the prompt that hit the context cache.
+    pub prompt_cache_hit_tokens: u64,
+    /// Number of tokens in the prompt that missed the context cache.
+    pub prompt_cache_miss_tokens: u64,
+    /// Total number of tokens used in the request (prompt + completion).
+    pub total_tokens: u64,
+    /// Breakdown of tokens used in the completion.
+    pub completion_tokens_details: Option<CompletionTokensDetails>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Usage {
+    /// Number of completion tokens spent on reasoning, `0` if the model does not reason.
+    #[must_use]
+    pub fn reasoning_tokens(&self) -> u64 {
+        self.completion_tokens_details
+            .as_ref()
+            .map_or(0, |details| details.reasoning_tokens)
+    }
+}
+
+impl std::ops::AddAssign<&Usage> for Usage {
+    fn add_assign(&mut self, rhs: &Usage) {
+        self.completion_tokens += rhs.completion_tokens;
+        self.prompt_tokens += rhs.prompt_tokens;
+        self.prompt_cache_hit_tokens += rhs.prompt_cache_hit_tokens;
+        self.prompt_cache_miss_tokens += rhs.prompt_cache_miss_tokens;
+        self.total_tokens += rhs.total_tokens;
+        if let Some(rhs) = &rhs.completion_tokens_details {
+            self.completion_tokens_details
+                .get_or_insert_default()
+                .reasoning_tokens += rhs.reasoning_tokens;
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct CompletionTokensDetails {
-    reasoning_tokens: u64,
+    /// Number of tokens generated by the model for reasoning.
+    pub reasoning_tokens: u64,
 }
@@ -1,7 +1,11 @@
+pub mod completion;
+mod error;
 mod fields;
-pub mod no_streaming;
+mod models;
 pub mod streaming;
 mod user_balance;
 
-pub use fields::FinishReason;
-pub use user_balance::UserBalance;
+pub use error::{ErrorDetail, ErrorResponse};
+pub use fields::{CompletionTokensDetails, FinishReason, Usage};
+pub use models::{ModelInfo, ModelList};
+pub use user_balance::{BalanceInfo, Currency, UserBalance};
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Model;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ModelList {
+    pub object: String,
+    pub data: Vec<ModelInfo>,
+}
+
+/// A model listed by [`Client::list_models`](crate::Client::list_models).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ModelInfo {
+    /// The model ID, [`Model::Other`] for models this crate does not know.
+    pub id: Model,
+    pub object: String,
+    /// The organization that owns the model.
+    pub owned_by: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_models_are_listed() {
+        let body = r#"{"object":"list","data":[{"id":"deepseek-chat","object":"model","owned_by":"deepseek"},{"id":"deepseek-coder-v3","object":"model","owned_by":"deepseek"}]}"#;
+        let list: ModelList = serde_json::from_str(body).unwrap();
+
+        let ids = list
+            .data
+            .into_iter()
+            .map(|model| model.id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            [
+                Model::DeepSeekChat,
+                Model::Other("deepseek-coder-v3".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn model_ids_round_trip() {
+        for model in [
+            Model::DeepSeekChat,
+            Model::DeepSeekReasoner,
+            Model::Other("deepseek-v4".to_string()),
+        ] {
+            let json = serde_json::to_string(&model).unwrap();
+            assert_eq!(json, format!("\"{}\"", model.id()));
+            assert_eq!(serde_json::from_str::<Model>(&json).unwrap(), model);
+            assert_eq!(Model::from(model.id()), model);
+        }
+    }
+}
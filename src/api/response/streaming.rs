@@ -3,7 +3,10 @@ use serde_json::Value;
 
 use crate::{
     Model, Role,
-    api::{ToolCallType, response::fields::FinishReason},
+    api::{
+        ToolCallType,
+        response::fields::{FinishReason, Usage},
+    },
 };
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -14,6 +17,8 @@ pub struct Chunk {
     pub model: Model,
     pub system_fingerprint: String,
     pub object: Object,
+    /// Only present in the last chunk, if requested via `stream_options.include_usage`.
+    pub usage: Option<Usage>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
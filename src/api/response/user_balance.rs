@@ -19,7 +19,7 @@ pub struct BalanceInfo {
     pub topped_up_balance: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Currency {
     #[serde(rename = "CNY")]
     Cny,
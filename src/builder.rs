@@ -0,0 +1,273 @@
+use crate::{
+    AuthScheme, Budget, CancelPolicy, Client, Error, Model, PriceTable, ResponseFormat,
+    RetryPolicy, RoundTripsExhausted, Tool, ToolChoice, message,
+};
+
+/// Builds a [`Client`], checking that the sampling parameters are within the ranges accepted by
+/// the API.
+///
+/// ```no_run
+/// # use deepseek_api::{Client, Model};
+/// let client = Client::builder(Model::DeepSeekChat, "sk-...")
+///     .temperature(0.2)
+///     .max_tokens(1024)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct ClientBuilder {
+    client: Client,
+}
+
+impl ClientBuilder {
+    #[must_use]
+    pub fn new(model: Model, api_key: &str) -> Self {
+        Self {
+            client: Client::new(model, api_key),
+        }
+    }
+
+    /// See [`Client::base_url`].
+    #[must_use]
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.client.base_url = base_url.into();
+        self
+    }
+
+    /// See [`Client::auth`].
+    #[must_use]
+    pub fn auth(mut self, auth: AuthScheme) -> Self {
+        self.client.auth = auth;
+        self
+    }
+
+    /// See [`Client::headers`].
+    #[must_use]
+    pub fn headers(mut self, headers: reqwest::header::HeaderMap) -> Self {
+        self.client.headers = headers;
+        self
+    }
+
+    /// See [`Client::frequency_penalty`]. Must be between -2.0 and 2.0.
+    #[must_use]
+    pub fn frequency_penalty(mut self, frequency_penalty: f32) -> Self {
+        self.client.frequency_penalty = frequency_penalty;
+        self
+    }
+
+    /// See [`Client::max_tokens`]. Must not exceed [`Model::max_output_tokens`].
+    #[must_use]
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.client.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// See [`Client::presence_penalty`]. Must be between -2.0 and 2.0.
+    #[must_use]
+    pub fn presence_penalty(mut self, presence_penalty: f32) -> Self {
+        self.client.presence_penalty = presence_penalty;
+        self
+    }
+
+    /// See [`Client::response_format`].
+    #[must_use]
+    pub fn response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.client.response_format = response_format;
+        self
+    }
+
+    /// See [`Client::temperature`]. Must be between 0.0 and 2.0.
+    #[must_use]
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.client.temperature = temperature;
+        self
+    }
+
+    /// See [`Client::top_p`]. Must be between 0.0 and 1.0.
+    #[must_use]
+    pub fn top_p(mut self, top_p: f32) -> Self {
+        self.client.top_p = top_p;
+        self
+    }
+
+    /// See [`Client::stop`]. At most [`Client::MAX_STOP_SEQUENCES`] sequences are allowed.
+    #[must_use]
+    pub fn stop(mut self, stop: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.client.stop = stop.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// See [`Client::context`].
+    #[must_use]
+    pub fn context(mut self, context: Vec<message::Message>) -> Self {
+        self.client.context = context;
+        self
+    }
+
+    /// See [`Client::tools`].
+    #[must_use]
+    pub fn tools(mut self, tools: Vec<Tool>) -> Self {
+        self.client.tools = tools;
+        self
+    }
+
+    /// See [`Client::tool_choice`]. A named tool must be registered with [`Self::tools`].
+    #[must_use]
+    pub fn tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.client.tool_choice = tool_choice;
+        self
+    }
+
+    /// See [`Client::pricing`].
+    #[must_use]
+    pub fn pricing(mut self, pricing: PriceTable) -> Self {
+        self.client.pricing = pricing;
+        self
+    }
+
+    /// See [`Client::budget`].
+    #[must_use]
+    pub fn budget(mut self, budget: Budget) -> Self {
+        self.client.budget = budget;
+        self
+    }
+
+    /// See [`Client::max_round_trips`].
+    #[must_use]
+    pub fn max_round_trips(mut self, max_round_trips: u32) -> Self {
+        self.client.max_round_trips = max_round_trips;
+        self
+    }
+
+    /// See [`Client::on_round_trips_exhausted`].
+    #[must_use]
+    pub fn on_round_trips_exhausted(
+        mut self,
+        on_round_trips_exhausted: RoundTripsExhausted,
+    ) -> Self {
+        self.client.on_round_trips_exhausted = on_round_trips_exhausted;
+        self
+    }
+
+    /// See [`Client::max_continuations`].
+    #[must_use]
+    pub fn max_continuations(mut self, max_continuations: u32) -> Self {
+        self.client.max_continuations = max_continuations;
+        self
+    }
+
+    /// See [`Client::max_argument_retries`].
+    #[must_use]
+    pub fn max_argument_retries(mut self, max_argument_retries: u32) -> Self {
+        self.client.max_argument_retries = max_argument_retries;
+        self
+    }
+
+    /// See [`Client::on_cancel`].
+    #[must_use]
+    pub fn on_cancel(mut self, on_cancel: CancelPolicy) -> Self {
+        self.client.on_cancel = on_cancel;
+        self
+    }
+
+    /// See [`Client::http_client`].
+    #[must_use]
+    pub fn http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.client.http_client = http_client;
+        self
+    }
+
+    /// See [`Client::retry`].
+    #[must_use]
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.client.retry = retry;
+        self
+    }
+
+    /// Builds the client, or returns [`Error::InvalidParameter`] for the first parameter that is
+    /// out of range.
+    pub fn build(self) -> Result<Client, Error> {
+        self.client.validate()?;
+        Ok(self.client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> ClientBuilder {
+        Client::builder(Model::DeepSeekChat, "sk-test")
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(builder().build().is_ok());
+    }
+
+    #[test]
+    fn out_of_range_sampling_parameters_are_rejected() {
+        let cases = [
+            ("temperature", builder().temperature(2.5)),
+            ("top_p", builder().top_p(-0.1)),
+            ("frequency_penalty", builder().frequency_penalty(3.0)),
+            ("presence_penalty", builder().presence_penalty(-2.1)),
+            ("temperature", builder().temperature(f32::NAN)),
+        ];
+
+        for (expected, builder) in cases {
+            match builder.build() {
+                Err(Error::InvalidParameter { name, .. }) => assert_eq!(name, expected),
+                _ => panic!("expected `{expected}` to be rejected"),
+            }
+        }
+    }
+
+    #[test]
+    fn too_many_stop_sequences_are_rejected() {
+        let stop = (0..=Client::MAX_STOP_SEQUENCES).map(|i| i.to_string());
+        match builder().stop(stop).build() {
+            Err(Error::InvalidParameter { name, .. }) => assert_eq!(name, "stop"),
+            _ => panic!("expected `stop` to be rejected"),
+        }
+        assert!(builder().stop(["\n\n", "END"]).build().is_ok());
+    }
+
+    #[test]
+    fn max_tokens_limit_depends_on_model() {
+        assert!(builder().max_tokens(0).build().is_err());
+        assert!(builder().max_tokens(8192).build().is_ok());
+        assert!(builder().max_tokens(8193).build().is_err());
+
+        let reasoner = Client::builder(Model::DeepSeekReasoner, "sk-test");
+        assert!(reasoner.max_tokens(65536).build().is_ok());
+    }
+
+    #[crate::tool]
+    /// Extracts the dates mentioned in a text.
+    async fn extract_dates(dates: Vec<String>) -> usize {
+        dates.len()
+    }
+
+    #[test]
+    fn tool_choice_must_match_the_registered_tools() {
+        let choice = |tool_choice| builder().tool_choice(tool_choice);
+        assert!(choice(ToolChoice::Required).build().is_err());
+        assert!(
+            choice(ToolChoice::Function("extract_dates".to_string()))
+                .build()
+                .is_err()
+        );
+
+        let with_tools = |tool_choice| choice(tool_choice).tools(vec![EXTRACT_DATES]);
+        assert!(with_tools(ToolChoice::Required).build().is_ok());
+        assert!(
+            with_tools(ToolChoice::Function("extract_dates".to_string()))
+                .build()
+                .is_ok()
+        );
+        match with_tools(ToolChoice::Function("extract_times".to_string())).build() {
+            Err(Error::InvalidParameter { name, .. }) => assert_eq!(name, "tool_choice"),
+            _ => panic!("expected `tool_choice` to be rejected"),
+        }
+    }
+}
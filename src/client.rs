@@ -1,22 +1,44 @@
+use std::pin::pin;
+#[cfg(feature = "nightly")]
 use std::{async_iter::AsyncIterator, pin::Pin};
 
+use async_stream::try_stream;
+use futures_core::Stream;
+
 use crate::{
-    Delta, FinishReason, Model, ResponseFormat, Tool, message,
+    AuthScheme, Budget, BudgetLimit, CancelPolicy, ChatOptions, ChatResponse, ChatStream,
+    ClientBuilder, Currency, Delta, Error, FimOptions, FinishReason, Model, PriceTable,
+    ResponseFormat, RetryAttempt, RetryPolicy, RoundTrip, RoundTripsExhausted, Tool, ToolChoice,
+    Usage, UsageTotals,
     api::{
-        request::{self, ChatCompletionRequest},
+        request::{self, ChatCompletionRequest, CompletionRequest, StreamOptions},
         response::{
-            UserBalance, no_streaming,
+            ModelInfo, ModelList, UserBalance,
+            completion::Completion,
             streaming::{self, Chunk},
         },
     },
+    message,
+    stream::{body_chunks, json_events, next},
+    turn::Turn,
 };
 
-const BASE_URL: &str = "https://api.deepseek.com";
-
 pub struct Client {
     pub model: Model,
     pub api_key: String,
 
+    /// The URL that endpoint paths such as `/chat/completions` are appended to.
+    ///
+    /// Defaults to [`Client::BASE_URL`]. Point it at a gateway, a mock server or any
+    /// OpenAI-compatible server to use those instead.
+    pub base_url: String,
+
+    /// How the API key is sent with each request.
+    pub auth: AuthScheme,
+
+    /// Extra headers sent with every request.
+    pub headers: reqwest::header::HeaderMap,
+
     /// Number between -2.0 and 2.0. Positive values penalize new tokens based on their existing frequency in the text so far, decreasing the model's likelihood to repeat the same line verbatim.
     pub frequency_penalty: f32,
 
@@ -45,247 +67,847 @@ pub struct Client {
     /// We generally recommend altering this or `temperature` but not both.
     pub top_p: f32,
 
+    /// Up to [`Client::MAX_STOP_SEQUENCES`] sequences where the API will stop generating further tokens.
+    ///
+    /// When one is hit, the answer ends with [`FinishReason::Stop`] and does not contain the sequence.
+    pub stop: Vec<String>,
+
     pub context: Vec<message::Message>,
 
     pub tools: Vec<Tool>,
+
+    /// Whether and which tools the model calls.
+    pub tool_choice: ToolChoice,
+
+    /// Token usage of every request sent by this client.
+    pub usage: UsageTotals,
+
+    /// Token prices used by [`Client::cost`].
+    pub pricing: PriceTable,
+
+    /// A limit on [`Self::usage`]. Calls stop sending requests once it is reached.
+    pub budget: Budget,
+
+    /// The maximum number of requests a single chat call sends while the model keeps calling tools.
+    pub max_round_trips: u32,
+
+    /// What a chat call does once [`Self::max_round_trips`] requests have been sent.
+    pub on_round_trips_exhausted: RoundTripsExhausted,
+
+    /// How many times a chat call continues an answer that was cut off with
+    /// [`FinishReason::Length`], by sending it back as a prefix for the model to complete.
+    ///
+    /// Each continuation is a round-trip of its own. Defaults to `0`, which returns cut-off
+    /// answers as they are. Continuing uses the beta
+    /// [chat prefix completion](https://api-docs.deepseek.com/guides/chat_prefix_completion),
+    /// so [`Self::base_url`] must also serve `/beta/chat/completions`.
+    pub max_continuations: u32,
+
+    /// How many times per chat call the model may call a tool with malformed arguments.
+    ///
+    /// Up to this limit, the parsing error is sent back to the model as the tool result so it
+    /// can correct its arguments. Beyond it, the call fails with [`Error::ToolArguments`].
+    pub max_argument_retries: u32,
+
+    /// What happens to [`Self::context`] when a chat call fails or its stream is dropped before
+    /// the model has answered.
+    pub on_cancel: CancelPolicy,
+
+    /// The HTTP client used for every request, so connections are pooled across calls.
+    ///
+    /// Replace it to configure proxies, root certificates or timeouts.
+    pub http_client: reqwest::Client,
+
+    /// How requests that were rate limited, rejected by an overloaded server or could not connect
+    /// are retried.
+    pub retry: RetryPolicy,
 }
 
 impl Client {
+    /// The official DeepSeek API endpoint.
+    pub const BASE_URL: &str = "https://api.deepseek.com";
+
+    /// The maximum number of stop sequences accepted by the API.
+    pub const MAX_STOP_SEQUENCES: usize = 16;
+
+    /// The maximum `max_tokens` of a FIM completion.
+    pub const MAX_FIM_TOKENS: u32 = 4096;
+
+    /// The maximum number of most likely tokens whose log probabilities can be requested.
+    pub const MAX_LOGPROBS: u32 = 20;
+
     #[must_use]
     pub fn new(model: Model, api_key: &str) -> Self {
+        Self::with_http_client(model, api_key, reqwest::Client::new())
+    }
+
+    /// Creates a client that sends its requests through the given `http_client`.
+    #[must_use]
+    pub fn with_http_client(model: Model, api_key: &str, http_client: reqwest::Client) -> Self {
         Self {
             model,
             api_key: api_key.to_string(),
+            base_url: Self::BASE_URL.to_string(),
+            auth: AuthScheme::Bearer,
+            headers: reqwest::header::HeaderMap::new(),
             frequency_penalty: 0.0,
             max_tokens: None,
             presence_penalty: 0.0,
             response_format: ResponseFormat::Text,
             temperature: 1.0,
             top_p: 1.0,
+            stop: Vec::new(),
             context: Vec::new(),
             tools: Vec::new(),
+            tool_choice: ToolChoice::default(),
+            usage: UsageTotals::default(),
+            pricing: PriceTable::default(),
+            budget: Budget::unlimited(),
+            max_round_trips: 32,
+            on_round_trips_exhausted: RoundTripsExhausted::default(),
+            max_continuations: 0,
+            max_argument_retries: 3,
+            on_cancel: CancelPolicy::default(),
+            http_client,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Creates a [`ClientBuilder`], which validates the parameters when building the client.
+    #[must_use]
+    pub fn builder(model: Model, api_key: &str) -> ClientBuilder {
+        ClientBuilder::new(model, api_key)
+    }
+
+    /// The estimated cost of [`Self::usage`] according to [`Self::pricing`], or `None` if a model
+    /// that was used has no price in `currency`.
+    #[must_use]
+    pub fn cost(&self, currency: Currency) -> Option<f64> {
+        self.usage.cost(&self.pricing, currency)
+    }
+
+    /// Checks that the sampling parameters are within the ranges accepted by the API.
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_sampling(
+            &self.model,
+            self.frequency_penalty,
+            self.max_tokens,
+            self.presence_penalty,
+            self.temperature,
+            self.top_p,
+        )?;
+        validate_stop(&self.stop)?;
+        validate_tool_choice(&self.tool_choice, &self.tools)?;
+        for tool in &self.tools {
+            tool.schema()?;
         }
+        Ok(())
     }
 
-    async fn send_chat_request(&self, stream: bool) -> reqwest::Response {
+    /// Sends a streaming chat completion request for the current context.
+    ///
+    /// If `prefix` is given, it is appended as the last message and the model continues it
+    /// through the beta endpoint.
+    async fn send_chat_request(
+        &self,
+        options: &ChatOptions,
+        prefix: Option<&message::Assistant>,
+    ) -> Result<reqwest::Response, Error> {
+        let model = options.model.as_ref().unwrap_or(&self.model);
+        let frequency_penalty = options.frequency_penalty.unwrap_or(self.frequency_penalty);
+        let max_tokens = options.max_tokens.or(self.max_tokens);
+        let presence_penalty = options.presence_penalty.unwrap_or(self.presence_penalty);
+        let temperature = options.temperature.unwrap_or(self.temperature);
+        let top_p = options.top_p.unwrap_or(self.top_p);
+        validate_sampling(
+            model,
+            frequency_penalty,
+            max_tokens,
+            presence_penalty,
+            temperature,
+            top_p,
+        )?;
+        let stop = options.stop.as_ref().unwrap_or(&self.stop);
+        validate_stop(stop)?;
+        let tools = self.tools_for(options);
+        let tool_choice = options.tool_choice.as_ref().unwrap_or(&self.tool_choice);
+        validate_tool_choice(tool_choice, tools)?;
+
+        let mut messages = self
+            .context
+            .iter()
+            .map(request::Message::from)
+            .collect::<Vec<_>>();
+        if let Some(prefix) = prefix {
+            let mut prefix = request::Message::from(&prefix.clone().into());
+            if let request::Message::Assistant { prefix, .. } = &mut prefix {
+                *prefix = true;
+            }
+            messages.push(prefix);
+        }
+
         let body = ChatCompletionRequest {
-            model: self.model.clone(),
-            messages: self.context.iter().map(request::Message::from).collect(),
-            stream,
-            frequency_penalty: Some(self.frequency_penalty),
-            max_tokens: self.max_tokens,
-            presence_penalty: Some(self.presence_penalty),
-            response_format: self.response_format.clone().into(),
-            temperature: self.temperature,
-            top_p: self.top_p,
-            tools: self.tools.iter().map(|tool| tool.into()).collect(),
+            model: model.clone(),
+            messages,
+            stream: true,
+            stream_options: Some(StreamOptions {
+                include_usage: true,
+            }),
+            frequency_penalty: Some(frequency_penalty),
+            max_tokens,
+            presence_penalty: Some(presence_penalty),
+            response_format: options
+                .response_format
+                .as_ref()
+                .unwrap_or(&self.response_format)
+                .clone()
+                .into(),
+            stop: (!stop.is_empty()).then(|| stop.clone()),
+            temperature,
+            top_p,
+            tools: tools
+                .iter()
+                .map(request::Tool::try_from)
+                .collect::<Result<_, _>>()?,
+            tool_choice: (!tools.is_empty()).then(|| tool_choice.into()),
         };
 
-        reqwest::Client::new()
-            .post(format!("{BASE_URL}/chat/completions"))
-            .header("Content-Type", "application/json")
-            .header("Accept", "application/json")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .body(serde_json::to_string(&body).unwrap())
-            .send()
-            .await
-            .unwrap()
+        let body = serde_json::to_string(&body)?;
+        let path = if prefix.is_some() || tools.iter().any(|tool| tool.strict) {
+            "/beta/chat/completions"
+        } else {
+            "/chat/completions"
+        };
+        self.send(|| {
+            self.request(reqwest::Method::POST, path)
+                .header("Content-Type", "application/json")
+                .body(body.clone())
+        })
+        .await
     }
 
-    pub async fn chat(&mut self, message: &str) -> Vec<message::Message> {
-        self.context.push(
-            message::User {
-                name: None,
-                content: message.to_string(),
-            }
-            .into(),
-        );
+    /// Starts a request to `path` with the base URL, authentication and extra headers applied.
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let url = format!("{}{path}", self.base_url.trim_end_matches('/'));
+        let builder = self
+            .http_client
+            .request(method, url)
+            .header("Accept", "application/json")
+            .headers(self.headers.clone());
 
-        let start_index = self.context.len();
+        match &self.auth {
+            AuthScheme::Bearer => builder.bearer_auth(&self.api_key),
+            AuthScheme::Header(name) => builder.header(name, &self.api_key),
+            AuthScheme::None => builder,
+        }
+    }
 
+    /// Sends the request built by `request`, retrying according to [`Self::retry`].
+    async fn send(
+        &self,
+        request: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, Error> {
+        let mut attempt = 1;
         loop {
-            let resp = self.send_chat_request(false).await;
-            let resp: no_streaming::Response = resp.json().await.unwrap();
+            let (error, retry_after) = match request().send().await {
+                Ok(resp) if resp.status().is_success() => return Ok(resp),
+                Ok(resp) => {
+                    let retry_after = crate::retry::retry_after(&resp);
+                    let status = resp.status();
+                    let body = resp.text().await?;
+                    (Error::from_response(status, body), retry_after)
+                }
+                Err(err) => (Error::from(err), None),
+            };
 
-            assert_eq!(resp.choices.len(), 1);
-            let choice = &resp.choices[0];
+            let Some(delay) = self.retry.next_delay(attempt, &error, retry_after) else {
+                return Err(error);
+            };
+            self.retry.notify(&RetryAttempt {
+                attempt,
+                delay,
+                error: &error,
+            });
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
 
-            self.context.push(
-                message::Assistant {
-                    name: None,
-                    content: choice.message.content.to_owned(),
-                    reasoning_content: choice.message.reasoning_content.to_owned(),
-                    tool_calls: choice.message.tool_calls.clone().map(|tool_calls| {
-                        tool_calls
-                            .iter()
-                            .map(|tool_call| message::ToolCall {
-                                id: tool_call.id.clone(),
-                                function: message::Function {
-                                    name: tool_call.function.name.clone(),
-                                    arguments: tool_call.function.arguments.clone(),
-                                },
-                            })
-                            .collect()
-                    }),
-                }
-                .into(),
-            );
-
-            if let Some(ref tool_calls) = choice.message.tool_calls {
-                for tool_call in tool_calls {
-                    let tool = self
-                        .tools
-                        .iter()
-                        .find(|tool| tool.name == tool_call.function.name.as_str())
-                        .unwrap();
-                    self.context.push(
-                        message::Tool {
-                            tool_call_id: tool_call.id.clone(),
-                            content: (tool.call)(tool_call.function.arguments.clone()).await,
-                        }
-                        .into(),
-                    );
-                }
-            }
+    /// Returns the first budget limit reached by the client or by the current call.
+    fn budget_reached(
+        &self,
+        call_usage: &UsageTotals,
+        options: &ChatOptions,
+    ) -> Option<BudgetLimit> {
+        self.budget
+            .reached(&self.usage, &self.pricing)
+            .or_else(|| options.budget?.reached(call_usage, &self.pricing))
+    }
+
+    /// Whether an answer that ended with `finish_reason` is continued in another round-trip.
+    fn should_continue(
+        &self,
+        options: &ChatOptions,
+        answer: &message::Assistant,
+        finish_reason: Option<FinishReason>,
+        continuations: u32,
+    ) -> bool {
+        // Cut-off tool call arguments cannot be continued by prefix completion.
+        finish_reason == Some(FinishReason::Length)
+            && answer.tool_calls.is_none()
+            && continuations < options.max_continuations.unwrap_or(self.max_continuations)
+    }
+
+    /// Stops forcing tool calls once the first request of a call has been sent, so the model can
+    /// answer with the tool results.
+    fn release_tool_choice(&self, options: &mut ChatOptions) {
+        if matches!(
+            options.tool_choice.as_ref().unwrap_or(&self.tool_choice),
+            ToolChoice::Required | ToolChoice::Function(_)
+        ) {
+            options.tool_choice = Some(ToolChoice::Auto);
+        }
+    }
 
-            match choice.finish_reason {
-                FinishReason::ToolCalls => continue,
-                _ => break,
+    fn round_trip_limit(&self, options: &ChatOptions) -> (u32, RoundTripsExhausted) {
+        (
+            options.max_round_trips.unwrap_or(self.max_round_trips),
+            options
+                .on_round_trips_exhausted
+                .unwrap_or(self.on_round_trips_exhausted),
+        )
+    }
+
+    fn tools_for<'a>(&'a self, options: &'a ChatOptions) -> &'a [Tool] {
+        options.tools.as_deref().unwrap_or(&self.tools)
+    }
+
+    /// Calls the tool requested by `tool_call` and returns its result.
+    ///
+    /// If the arguments are malformed, the error is returned as the result so the model can
+    /// correct itself, until `argument_errors` reaches the call's retry limit.
+    async fn call_tool(
+        &self,
+        tool_call: &message::ToolCall,
+        options: &ChatOptions,
+        argument_errors: &mut u32,
+    ) -> Result<String, Error> {
+        let name = tool_call.function.name.as_str();
+        let tool = self
+            .tools_for(options)
+            .iter()
+            .find(|tool| tool.name == name)
+            .ok_or_else(|| Error::UnknownTool {
+                name: name.to_string(),
+            })?;
+
+        match (tool.call)(tool_call.function.arguments.clone()).await {
+            Ok(content) => Ok(content),
+            Err(err)
+                if *argument_errors
+                    < options
+                        .max_argument_retries
+                        .unwrap_or(self.max_argument_retries) =>
+            {
+                *argument_errors += 1;
+                Ok(format!(
+                    "Error: the arguments do not match the parameters of `{name}`: {err}. \
+                     Call the tool again with corrected arguments."
+                ))
             }
+            Err(source) => Err(Error::ToolArguments {
+                name: name.to_string(),
+                source,
+            }),
         }
+    }
+
+    /// Sends `message` and runs the tool-call loop until the model answers.
+    pub async fn chat(&mut self, message: &str) -> Result<ChatResponse, Error> {
+        self.chat_with(message, ChatOptions::default()).await
+    }
 
-        self.context[start_index..].to_vec()
+    /// Like [`Self::chat`], but with the client's settings overridden by `options` for this call.
+    pub async fn chat_with(
+        &mut self,
+        message: &str,
+        options: ChatOptions,
+    ) -> Result<ChatResponse, Error> {
+        self.chat_with_callback(message, options, |_| {}).await
     }
 
+    /// Sends `message` and lets the model continue its answer from `prefix`, e.g. the opening of
+    /// a code block or a JSON skeleton.
+    ///
+    /// The answer in the returned messages starts with `prefix`. See [`ChatOptions::prefix`].
+    pub async fn chat_with_prefix(
+        &mut self,
+        message: &str,
+        prefix: &str,
+    ) -> Result<ChatResponse, Error> {
+        let options = ChatOptions {
+            prefix: Some(prefix.to_string()),
+            ..ChatOptions::default()
+        };
+        self.chat_with(message, options).await
+    }
+
+    #[cfg(feature = "nightly")]
     #[must_use]
     pub async fn streaming_chat(
         &mut self,
         message: &str,
-    ) -> Pin<Box<impl AsyncIterator<Item = Delta>>> {
-        self.context.push(
-            message::User {
-                name: None,
-                content: message.to_string(),
+    ) -> Pin<Box<impl AsyncIterator<Item = Result<Delta, Error>>>> {
+        self.streaming_chat_with(message, ChatOptions::default())
+            .await
+    }
+
+    /// Like [`Self::streaming_chat`], but with the client's settings overridden by `options` for
+    /// this call.
+    #[cfg(feature = "nightly")]
+    #[must_use]
+    pub async fn streaming_chat_with(
+        &mut self,
+        message: &str,
+        options: ChatOptions,
+    ) -> Pin<Box<impl AsyncIterator<Item = Result<Delta, Error>>>> {
+        Box::pin(crate::stream::IntoAsyncIterator::new(
+            self.deltas(message, options),
+        ))
+    }
+
+    /// Sends `message` and streams the answer, running the tool-call loop until the model
+    /// answers.
+    ///
+    /// The client's settings can be overridden by `options` for this call. The stream ends after
+    /// yielding the first error. If it ends early, by an error or by being dropped or
+    /// [cancelled](ChatStream::cancel), the context is updated according to [`Self::on_cancel`].
+    pub fn chat_stream(&mut self, message: &str, options: ChatOptions) -> ChatStream<'_> {
+        ChatStream::new(self.deltas(message, options))
+    }
+
+    /// Like [`Self::chat_with`], but passes every delta of the answer to `on_delta` while it
+    /// arrives.
+    ///
+    /// Returns the same [`ChatResponse`] as [`Self::chat_with`] once the model has answered.
+    pub async fn chat_with_callback(
+        &mut self,
+        message: &str,
+        options: ChatOptions,
+        mut on_delta: impl FnMut(&Delta),
+    ) -> Result<ChatResponse, Error> {
+        // The user message is pushed first, the messages of the call come after it.
+        let start_index = self.context.len() + 1;
+        let mut round_trips = Vec::new();
+        let mut usage = Usage::default();
+
+        let mut stream = self.chat_stream(message, options);
+        while let Some(delta) = next(&mut stream).await {
+            let delta = delta?;
+            on_delta(&delta);
+            if let Delta::Finished(round_trip) = delta {
+                usage += &round_trip.usage;
+                round_trips.push(round_trip);
             }
-            .into(),
-        );
+        }
+        drop(stream);
+
+        Ok(ChatResponse {
+            messages: self.context[start_index..].to_vec(),
+            round_trips,
+            usage,
+        })
+    }
+
+    /// Sends the current [`Self::context`] as a single streaming request and returns the raw
+    /// chunks of the response.
+    ///
+    /// Unlike [`Self::chat_stream`], tools are not called and the context is not updated. The
+    /// usage is added to [`Self::usage`], and no request is sent once a budget is reached.
+    pub fn chunk_stream(
+        &mut self,
+        options: ChatOptions,
+    ) -> impl Stream<Item = Result<Chunk, Error>> + Send + Unpin {
+        Box::pin(try_stream! {
+            if let Some(limit) = self.budget_reached(&UsageTotals::default(), &options) {
+                Err(Error::BudgetExceeded {
+                    limit,
+                    transcript: Vec::new(),
+                })?;
+            }
+
+            let resp = self.send_chat_request(&options, None).await?;
+            let mut chunks = pin!(json_events::<Chunk>(body_chunks(resp)));
+            while let Some(chunk) = next(&mut chunks).await {
+                let chunk = chunk?;
+                if let Some(usage) = &chunk.usage {
+                    let model = options.model.clone().unwrap_or_else(|| self.model.clone());
+                    self.usage.record(&model, usage);
+                }
+                yield chunk;
+            }
+        })
+    }
+
+    /// Sends a streaming request for the current context and yields the decoded chunks.
+    fn chunks(
+        &self,
+        options: &ChatOptions,
+        prefix: Option<message::Assistant>,
+    ) -> impl Stream<Item = Result<Chunk, Error>> {
+        try_stream! {
+            let resp = self.send_chat_request(options, prefix.as_ref()).await?;
+            let mut chunks = pin!(json_events(body_chunks(resp)));
+            while let Some(chunk) = next(&mut chunks).await {
+                yield chunk?;
+            }
+        }
+    }
+
+    /// Completes the code between `prompt` and `suffix` with the beta fill-in-the-middle (FIM)
+    /// completion endpoint.
+    ///
+    /// The context is not used or updated. The usage is added to [`Self::usage`], and no request
+    /// is sent once [`Self::budget`] is reached.
+    pub async fn fim_completion(
+        &mut self,
+        prompt: &str,
+        suffix: &str,
+        options: FimOptions,
+    ) -> Result<Completion, Error> {
+        self.check_fim_budget()?;
+        let resp = self
+            .send_fim_request(false, prompt, suffix, &options)
+            .await?;
+        let completion: Completion = serde_json::from_slice(&resp.bytes().await?)?;
+        if let Some(usage) = &completion.usage {
+            self.record_fim_usage(&options, usage);
+        }
+        Ok(completion)
+    }
+
+    /// Like [`Self::fim_completion`], but streams the completion as chunks.
+    pub fn fim_completion_stream(
+        &mut self,
+        prompt: &str,
+        suffix: &str,
+        options: FimOptions,
+    ) -> impl Stream<Item = Result<Completion, Error>> + Send + Unpin {
+        let prompt = prompt.to_string();
+        let suffix = suffix.to_string();
+        Box::pin(try_stream! {
+            self.check_fim_budget()?;
+            let resp = self.send_fim_request(true, &prompt, &suffix, &options).await?;
+            let mut chunks = pin!(json_events::<Completion>(body_chunks(resp)));
+            while let Some(chunk) = next(&mut chunks).await {
+                let chunk = chunk?;
+                if let Some(usage) = &chunk.usage {
+                    self.record_fim_usage(&options, usage);
+                }
+                yield chunk;
+            }
+        })
+    }
+
+    fn check_fim_budget(&self) -> Result<(), Error> {
+        match self.budget.reached(&self.usage, &self.pricing) {
+            Some(limit) => Err(Error::BudgetExceeded {
+                limit,
+                transcript: Vec::new(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn record_fim_usage(&mut self, options: &FimOptions, usage: &Usage) {
+        let model = options.model.clone().unwrap_or_else(|| self.model.clone());
+        self.usage.record(&model, usage);
+    }
+
+    async fn send_fim_request(
+        &self,
+        stream: bool,
+        prompt: &str,
+        suffix: &str,
+        options: &FimOptions,
+    ) -> Result<reqwest::Response, Error> {
+        let model = options.model.as_ref().unwrap_or(&self.model);
+        let frequency_penalty = options.frequency_penalty.unwrap_or(self.frequency_penalty);
+        let presence_penalty = options.presence_penalty.unwrap_or(self.presence_penalty);
+        let temperature = options.temperature.unwrap_or(self.temperature);
+        let top_p = options.top_p.unwrap_or(self.top_p);
+        validate_sampling(
+            model,
+            frequency_penalty,
+            None,
+            presence_penalty,
+            temperature,
+            top_p,
+        )?;
+        if let Some(max_tokens) = options.max_tokens
+            && !(1..=Self::MAX_FIM_TOKENS).contains(&max_tokens)
+        {
+            return Err(Error::InvalidParameter {
+                name: "max_tokens",
+                reason: format!("{max_tokens} is not between 1 and {}", Self::MAX_FIM_TOKENS),
+            });
+        }
+        if let Some(logprobs) = options.logprobs
+            && logprobs > Self::MAX_LOGPROBS
+        {
+            return Err(Error::InvalidParameter {
+                name: "logprobs",
+                reason: format!("{logprobs} is greater than {}", Self::MAX_LOGPROBS),
+            });
+        }
+        validate_stop(options.stop.as_deref().unwrap_or_default())?;
 
-        let mut finish_reason: Option<FinishReason> = None;
+        let body = CompletionRequest {
+            model: model.clone(),
+            prompt: prompt.to_string(),
+            suffix: (!suffix.is_empty()).then(|| suffix.to_string()),
+            echo: options.echo,
+            frequency_penalty,
+            logprobs: options.logprobs,
+            max_tokens: options.max_tokens,
+            presence_penalty,
+            stop: options.stop.clone(),
+            stream,
+            stream_options: stream.then_some(StreamOptions {
+                include_usage: true,
+            }),
+            temperature,
+            top_p,
+        };
+
+        let body = serde_json::to_string(&body)?;
+        self.send(|| {
+            self.request(reqwest::Method::POST, "/beta/completions")
+                .header("Content-Type", "application/json")
+                .body(body.clone())
+        })
+        .await
+    }
+
+    fn deltas<'a>(
+        &'a mut self,
+        message: &str,
+        mut options: ChatOptions,
+    ) -> impl Stream<Item = Result<Delta, Error>> + use<'a> {
+        // Begun eagerly so that a stream dropped before it is polled still applies the policy.
+        let on_cancel = options.on_cancel.unwrap_or(self.on_cancel);
+        let mut turn = Turn::begin(self, message, on_cancel);
+        if let Some(prefix) = options.prefix.take() {
+            turn.prefill(prefix);
+        }
+
+        try_stream! {
+            let mut call_usage = UsageTotals::default();
+            let (max_round_trips, on_exhausted) = turn.client.round_trip_limit(&options);
+            let mut round_trips = 0;
+            let mut forcing_answer = false;
+            let mut continuations = 0;
+            let mut argument_errors = 0;
 
-        Box::pin(async gen move {
             loop {
-                let mut resp = self.send_chat_request(true).await;
+                if let Some(limit) = turn.client.budget_reached(&call_usage, &options) {
+                    Err(Error::BudgetExceeded {
+                        limit,
+                        transcript: turn.transcript(),
+                    })?;
+                }
 
-                let mut assistant_msg = message::Assistant {
+                if round_trips == max_round_trips {
+                    match on_exhausted {
+                        RoundTripsExhausted::Error => {
+                            Err(Error::TooManyRoundTrips {
+                                max: max_round_trips,
+                                transcript: turn.transcript(),
+                            })?;
+                        }
+                        RoundTripsExhausted::ForceAnswer => {
+                            forcing_answer = true;
+                            options.tools = Some(Vec::new());
+                            options.tool_choice = Some(ToolChoice::None);
+                        }
+                        RoundTripsExhausted::ReturnPartial => {
+                            if let Some(answer) = turn.partial.take() {
+                                turn.client.context.push(answer.into());
+                            }
+                            break;
+                        }
+                    }
+                }
+                round_trips += 1;
+
+                let mut finish_reason: Option<FinishReason> = None;
+                let mut usage: Option<Usage> = None;
+                let mut metadata: Option<(String, u64, String)> = None;
+                // Kept in the turn so that it can be committed if the stream is cancelled. A
+                // cut-off answer being continued is already there.
+                let prefix = turn.partial.clone();
+                let assistant_msg = turn.partial.get_or_insert_with(|| message::Assistant {
                     name: None,
                     content: String::new(),
                     reasoning_content: None,
                     tool_calls: None,
-                };
+                    truncated: false,
+                });
 
-                while let Some(chunk) = resp.chunk().await.unwrap() {
-                    let s = String::from_utf8(chunk.to_vec()).unwrap();
-                    for data in crate::stream::parse_sse_data_frames(&s) {
-                        let chunk: Chunk = serde_json::from_str(data).unwrap();
+                // Scoped so that the borrow of the client ends before the context is updated.
+                {
+                    let mut chunks = pin!(turn.client.chunks(&options, prefix));
+                    while let Some(chunk) = next(&mut chunks).await {
+                        let chunk = chunk?;
+                        metadata.get_or_insert_with(|| {
+                            (chunk.id.clone(), chunk.created, chunk.system_fingerprint.clone())
+                        });
+                        if chunk.usage.is_some() {
+                            usage = chunk.usage;
+                        }
                         for choice in chunk.choices {
-                            match choice.finish_reason {
-                                Some(fr) => finish_reason = Some(fr),
-                                None => match choice.delta {
-                                    streaming::Delta::Assistant {
-                                        content,
-                                        reasoning_content,
-                                        role,
-                                    } => {
-                                        if let Some(reasoning_content) = reasoning_content
-                                            && !reasoning_content.is_empty()
-                                        {
-                                            assistant_msg
-                                                .reasoning_content
-                                                .get_or_insert_default()
-                                                .push_str(&reasoning_content);
-                                            yield Delta::Thinking {
-                                                reasoning_content,
-                                                role: role.clone(),
-                                            }
+                            if choice.finish_reason.is_some() {
+                                finish_reason = choice.finish_reason;
+                            }
+                            match choice.delta {
+                                streaming::Delta::Assistant {
+                                    content,
+                                    reasoning_content,
+                                    role,
+                                } => {
+                                    if let Some(reasoning_content) = reasoning_content
+                                        && !reasoning_content.is_empty()
+                                    {
+                                        assistant_msg
+                                            .reasoning_content
+                                            .get_or_insert_default()
+                                            .push_str(&reasoning_content);
+                                        yield Delta::Thinking {
+                                            reasoning_content,
+                                            role: role.clone(),
                                         }
+                                    }
 
-                                        if let Some(content) = content
-                                            && !content.is_empty()
+                                    if let Some(content) = content
+                                        && !content.is_empty()
+                                    {
+                                        assistant_msg.content.push_str(&content);
+                                        yield Delta::Content { content, role }
+                                    }
+                                }
+                                streaming::Delta::ToolCall {
+                                    tool_calls: tool_call_deltas,
+                                } => {
+                                    for tool_call_delta in tool_call_deltas {
+                                        let tool_calls =
+                                            assistant_msg.tool_calls.get_or_insert_default();
+
+                                        if tool_call_delta.index == tool_calls.len() {
+                                            let (id, name) = tool_call_delta
+                                                .id
+                                                .clone()
+                                                .zip(tool_call_delta.function.name.clone())
+                                                .ok_or_else(|| {
+                                                    Error::Protocol(
+                                                        "tool call delta is missing its id or name"
+                                                            .to_string(),
+                                                    )
+                                                })?;
+                                            tool_calls.push(message::ToolCall {
+                                                id,
+                                                function: message::Function {
+                                                    name,
+                                                    arguments: tool_call_delta
+                                                        .function
+                                                        .arguments
+                                                        .clone(),
+                                                },
+                                            });
+                                        } else if let Some(tool_call) =
+                                            tool_calls.get_mut(tool_call_delta.index)
                                         {
-                                            assistant_msg.content.push_str(&content);
-                                            yield Delta::Content { content, role }
+                                            tool_call
+                                                .function
+                                                .arguments
+                                                .push_str(&tool_call_delta.function.arguments);
+                                        } else {
+                                            Err(Error::Protocol(format!(
+                                                "tool call delta has out-of-order index {}",
+                                                tool_call_delta.index
+                                            )))?;
                                         }
-                                    }
-                                    streaming::Delta::ToolCall {
-                                        tool_calls: tool_call_deltas,
-                                    } => {
-                                        for tool_call_delta in tool_call_deltas {
-                                            let tool_calls =
-                                                assistant_msg.tool_calls.get_or_insert_default();
-
-                                            if tool_call_delta.index == tool_calls.len() {
-                                                tool_calls.push(message::ToolCall {
-                                                    id: tool_call_delta.id.clone().unwrap(),
-                                                    function: message::Function {
-                                                        name: tool_call_delta
-                                                            .function
-                                                            .name
-                                                            .clone()
-                                                            .unwrap(),
-                                                        arguments: tool_call_delta
-                                                            .function
-                                                            .arguments
-                                                            .clone(),
-                                                    },
-                                                });
-                                            } else {
-                                                tool_calls[tool_call_delta.index]
-                                                    .function
-                                                    .arguments
-                                                    .push_str(&tool_call_delta.function.arguments);
-                                            }
-
-                                            yield Delta::ToolCallInput {
-                                                tool_call_id: tool_call_delta.id,
-                                                name: tool_call_delta.function.name,
-                                                arguments: tool_call_delta.function.arguments,
-                                            }
+
+                                        yield Delta::ToolCallInput {
+                                            tool_call_id: tool_call_delta.id,
+                                            name: tool_call_delta.function.name,
+                                            arguments: tool_call_delta.function.arguments,
                                         }
                                     }
-                                },
+                                }
                             }
                         }
                     }
                 }
 
-                self.context.push(assistant_msg.clone().into());
+                turn.client.release_tool_choice(&mut options);
+
+                if let Some(usage) = &usage {
+                    let model = options.model.as_ref().unwrap_or(&turn.client.model);
+                    call_usage.record(model, usage);
+                    turn.client.usage.record(model, usage);
+                    yield Delta::Usage(usage.clone());
+                }
+
+                if let Some((finish_reason, (id, created, system_fingerprint))) =
+                    finish_reason.zip(metadata)
+                {
+                    yield Delta::Finished(RoundTrip {
+                        id,
+                        created,
+                        system_fingerprint,
+                        finish_reason,
+                        usage: usage.unwrap_or_default(),
+                    });
+                }
+
+                let mut assistant_msg = turn
+                    .partial
+                    .take()
+                    .expect("the answer was inserted before streaming");
+                if forcing_answer {
+                    // Tools are disabled, so calls the model makes anyway are never answered, and
+                    // the API rejects a context with unanswered tool calls.
+                    assistant_msg.tool_calls = None;
+                }
+                if turn.client.should_continue(&options, &assistant_msg, finish_reason, continuations) {
+                    continuations += 1;
+                    turn.partial = Some(assistant_msg);
+                    continue;
+                }
+                turn.client.context.push(assistant_msg.clone().into());
 
                 match finish_reason {
-                    Some(FinishReason::ToolCalls) => {
-                        for tool_call in assistant_msg.tool_calls.unwrap() {
-                            let tool_call_id = tool_call.id;
-                            if self.context.iter().any(|msg| match msg {
-                                message::Message::Tool(tool) => {
-                                    tool.tool_call_id == tool_call_id
-                                }
+                    Some(FinishReason::ToolCalls) if !forcing_answer => {
+                        for tool_call in assistant_msg.tool_calls.unwrap_or_default() {
+                            let tool_call_id = tool_call.id.clone();
+                            if turn.client.context.iter().any(|msg| match msg {
+                                message::Message::Tool(tool) => tool.tool_call_id == tool_call_id,
                                 _ => false,
                             }) {
                                 continue;
                             }
 
-                            let tool = self
-                                .tools
-                                .iter()
-                                .find(|tool| tool.name == tool_call.function.name.as_str())
-                                .unwrap();
-                            let content = (tool.call)(tool_call.function.arguments).await;
+                            let content = turn
+                                .client
+                                .call_tool(&tool_call, &options, &mut argument_errors)
+                                .await?;
 
                             yield Delta::ToolCallOutput {
                                 tool_call_id: tool_call_id.clone(),
                                 content: content.clone(),
                             };
 
-                            self.context.push(
+                            turn.client.context.push(
                                 message::Tool {
                                     tool_call_id,
                                     content,
@@ -294,24 +916,516 @@ impl Client {
                             );
                         }
                     }
-                    None => unreachable!(),
+                    None => {
+                        Err(Error::Protocol(
+                            "stream ended without a finish reason".to_string(),
+                        ))?;
+                    }
                     _ => break,
                 }
             }
-        })
+
+            turn.finish();
+        }
+    }
+
+    /// Lists the models available to the API key.
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>, Error> {
+        let resp = self
+            .send(|| self.request(reqwest::Method::GET, "/models"))
+            .await?;
+        let list: ModelList = serde_json::from_slice(&resp.bytes().await?)?;
+        Ok(list.data)
     }
 
-    #[must_use]
     /// Get user current balance
-    pub async fn user_balance(&self) -> UserBalance {
-        let client = reqwest::Client::new();
-        let resp = client
-            .get(format!("{BASE_URL}/user/balance"))
-            .header("Accept", "application/json")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .send()
-            .await
-            .unwrap();
-        resp.json().await.unwrap()
+    pub async fn user_balance(&self) -> Result<UserBalance, Error> {
+        let resp = self
+            .send(|| self.request(reqwest::Method::GET, "/user/balance"))
+            .await?;
+        Ok(serde_json::from_slice(&resp.bytes().await?)?)
+    }
+}
+
+pub(crate) fn validate_sampling(
+    model: &Model,
+    frequency_penalty: f32,
+    max_tokens: Option<u32>,
+    presence_penalty: f32,
+    temperature: f32,
+    top_p: f32,
+) -> Result<(), Error> {
+    fn check_range(name: &'static str, value: f32, min: f32, max: f32) -> Result<(), Error> {
+        if (min..=max).contains(&value) {
+            Ok(())
+        } else {
+            Err(Error::InvalidParameter {
+                name,
+                reason: format!("{value} is not between {min} and {max}"),
+            })
+        }
+    }
+
+    check_range("frequency_penalty", frequency_penalty, -2.0, 2.0)?;
+    check_range("presence_penalty", presence_penalty, -2.0, 2.0)?;
+    check_range("temperature", temperature, 0.0, 2.0)?;
+    check_range("top_p", top_p, 0.0, 1.0)?;
+
+    if let Some(max_tokens) = max_tokens {
+        // The limit of unknown models is left to the server to enforce.
+        let limit = model.max_output_tokens().unwrap_or(u32::MAX);
+        if !(1..=limit).contains(&max_tokens) {
+            return Err(Error::InvalidParameter {
+                name: "max_tokens",
+                reason: format!("{max_tokens} is not between 1 and {limit} for {model}"),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_tool_choice(tool_choice: &ToolChoice, tools: &[Tool]) -> Result<(), Error> {
+    match tool_choice {
+        ToolChoice::Required if tools.is_empty() => Err(Error::InvalidParameter {
+            name: "tool_choice",
+            reason: "a tool call is required but no tools are registered".to_string(),
+        }),
+        ToolChoice::Function(name) if !tools.iter().any(|tool| tool.name == name) => {
+            Err(Error::InvalidParameter {
+                name: "tool_choice",
+                reason: format!("no tool named `{name}` is registered"),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn validate_stop(stop: &[String]) -> Result<(), Error> {
+    if stop.len() > Client::MAX_STOP_SEQUENCES {
+        return Err(Error::InvalidParameter {
+            name: "stop",
+            reason: format!(
+                "{} sequences given, at most {} are allowed",
+                stop.len(),
+                Client::MAX_STOP_SEQUENCES
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        Role,
+        message::Message,
+        mock::{MockServer, Response, answer, tool_call},
+        tool,
+    };
+
+    #[tool]
+    /// Adds two integers.
+    async fn add(a: i32, b: i32) -> i32 {
+        a + b
+    }
+
+    fn roles(messages: &[Message]) -> Vec<Role> {
+        messages.iter().map(Message::role).collect()
+    }
+
+    /// The last message of the context sent with `request`.
+    fn last_message(request: &crate::mock::Request) -> &serde_json::Value {
+        request.body["messages"].as_array().unwrap().last().unwrap()
+    }
+
+    #[tokio::test]
+    async fn exhausted_round_trips_fail_by_default() {
+        let server = MockServer::start(vec![
+            tool_call("call_0", "add", r#"{"a":1,"b":2}"#),
+            tool_call("call_1", "add", r#"{"a":3,"b":4}"#),
+        ])
+        .await;
+        let mut client = server.client();
+        client.tools = vec![ADD];
+        client.max_round_trips = 2;
+
+        let Err(Error::TooManyRoundTrips { max, transcript }) = client.chat("Add").await else {
+            panic!("expected the round-trips to be exhausted");
+        };
+        assert_eq!(max, 2);
+        use Role::*;
+        assert_eq!(roles(&transcript), vec![Assistant, Tool, Assistant, Tool]);
+        assert!(client.context.is_empty());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn exhausted_round_trips_can_force_an_answer() {
+        let server = MockServer::start(vec![
+            tool_call("call_0", "add", r#"{"a":1,"b":2}"#),
+            answer("1 + 2 = 3", "stop"),
+        ])
+        .await;
+        let mut client = server.client();
+        client.tools = vec![ADD];
+        client.max_round_trips = 1;
+        client.on_round_trips_exhausted = RoundTripsExhausted::ForceAnswer;
+
+        let response = client.chat("Add 1 and 2").await.unwrap();
+        assert_eq!(response.text(), Some("1 + 2 = 3"));
+        assert!(response.is_complete());
+        use Role::*;
+        assert_eq!(
+            roles(&client.context),
+            vec![User, Assistant, Tool, Assistant]
+        );
+
+        let requests = server.requests();
+        assert!(
+            requests
+                .iter()
+                .all(|request| request.path == "/chat/completions")
+        );
+        assert_eq!(requests[0].body["tools"].as_array().unwrap().len(), 1);
+        assert_eq!(requests[1].body["tools"], json!([]));
+        assert_eq!(requests[1].body.get("tool_choice"), None);
+        assert_eq!(last_message(&requests[1])["content"], "3");
+    }
+
+    #[tokio::test]
+    async fn client_budget_stops_the_tool_loop() {
+        let server = MockServer::start(vec![
+            tool_call("call_0", "add", r#"{"a":1,"b":2}"#),
+            tool_call("call_1", "add", r#"{"a":3,"b":4}"#),
+            tool_call("call_2", "add", r#"{"a":5,"b":6}"#),
+        ])
+        .await;
+        let mut client = server.client();
+        client.tools = vec![ADD];
+        client.budget = Budget {
+            max_tokens: Some(30),
+            max_cost: None,
+        };
+
+        let Err(Error::BudgetExceeded { limit, transcript }) = client.chat("Add").await else {
+            panic!("expected the budget to stop the call");
+        };
+        assert_eq!(limit, BudgetLimit::Tokens(30));
+        use Role::*;
+        assert_eq!(roles(&transcript), vec![Assistant, Tool, Assistant, Tool]);
+        assert_eq!(server.requests().len(), 2);
+        assert_eq!(client.usage.total.total_tokens, 30);
+        assert!(client.context.is_empty());
+
+        // The client's budget also covers later calls.
+        assert!(matches!(
+            client.chat("Add again").await,
+            Err(Error::BudgetExceeded { .. })
+        ));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn call_budget_stops_only_that_call() {
+        let server = MockServer::start(vec![
+            tool_call("call_0", "add", r#"{"a":1,"b":2}"#),
+            answer("3", "stop"),
+        ])
+        .await;
+        let mut client = server.client();
+        client.tools = vec![ADD];
+        let options = ChatOptions {
+            budget: Some(Budget {
+                max_tokens: Some(15),
+                max_cost: None,
+            }),
+            ..ChatOptions::default()
+        };
+
+        let Err(Error::BudgetExceeded { limit, transcript }) =
+            client.chat_with("Add", options).await
+        else {
+            panic!("expected the budget to stop the call");
+        };
+        assert_eq!(limit, BudgetLimit::Tokens(15));
+        use Role::*;
+        assert_eq!(roles(&transcript), vec![Assistant, Tool]);
+        assert_eq!(server.requests().len(), 1);
+
+        let response = client.chat("Add").await.unwrap();
+        assert_eq!(response.text(), Some("3"));
+    }
+
+    #[tokio::test]
+    async fn forced_answer_drops_tool_calls_it_cannot_answer() {
+        let server = MockServer::start(vec![
+            tool_call("call_0", "add", r#"{"a":1,"b":2}"#),
+            tool_call("call_1", "add", r#"{"a":3,"b":4}"#),
+        ])
+        .await;
+        let mut client = server.client();
+        client.tools = vec![ADD];
+        client.max_round_trips = 1;
+        client.on_round_trips_exhausted = RoundTripsExhausted::ForceAnswer;
+
+        let response = client.chat("Add 1 and 2").await.unwrap();
+        assert_eq!(response.finish_reason(), Some(FinishReason::ToolCalls));
+        use Role::*;
+        assert_eq!(
+            roles(&client.context),
+            vec![User, Assistant, Tool, Assistant]
+        );
+        let Some(Message::Assistant(last)) = client.context.last() else {
+            panic!("expected the forced answer to be committed");
+        };
+        assert!(last.tool_calls.is_none());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn exhausted_round_trips_can_return_partial_results() {
+        let server = MockServer::start(vec![tool_call("call_0", "add", r#"{"a":1,"b":2}"#)]).await;
+        let mut client = server.client();
+        client.tools = vec![ADD];
+        client.max_round_trips = 1;
+        client.on_round_trips_exhausted = RoundTripsExhausted::ReturnPartial;
+
+        let response = client.chat("Add 1 and 2").await.unwrap();
+        assert_eq!(response.finish_reason(), Some(FinishReason::ToolCalls));
+        assert!(!response.is_complete());
+        use Role::*;
+        assert_eq!(roles(&response.messages), vec![Assistant, Tool]);
+        assert_eq!(roles(&client.context), vec![User, Assistant, Tool]);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn cut_off_answer_is_continued_with_a_prefix() {
+        let server =
+            MockServer::start(vec![answer("Hello, ", "length"), answer("world!", "stop")]).await;
+        let mut client = server.client();
+        client.max_continuations = 1;
+
+        let response = client.chat("Greet the world").await.unwrap();
+        assert_eq!(response.text(), Some("Hello, world!"));
+        assert_eq!(response.round_trips.len(), 2);
+        assert!(response.is_complete());
+        use Role::*;
+        assert_eq!(roles(&client.context), vec![User, Assistant]);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/chat/completions");
+        assert_eq!(requests[1].path, "/beta/chat/completions");
+        assert_eq!(
+            last_message(&requests[1]),
+            &json!({
+                "role": "assistant",
+                "name": null,
+                "content": "Hello, ",
+                "reasoning_content": null,
+                "tool_calls": null,
+                "prefix": true,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn cut_off_answer_is_returned_without_continuations() {
+        let server = MockServer::start(vec![answer("Hello, ", "length")]).await;
+        let mut client = server.client();
+
+        let response = client.chat("Greet the world").await.unwrap();
+        assert_eq!(response.finish_reason(), Some(FinishReason::Length));
+        assert_eq!(response.text(), Some("Hello, "));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn malformed_arguments_are_sent_back_to_the_model() {
+        let server = MockServer::start(vec![
+            tool_call("call_0", "add", r#"{"a":"one","b":2}"#),
+            tool_call("call_1", "add", r#"{"a":1,"b":2}"#),
+            answer("3", "stop"),
+        ])
+        .await;
+        let mut client = server.client();
+        client.tools = vec![ADD];
+        client.max_argument_retries = 1;
+
+        let response = client.chat("Add one and 2").await.unwrap();
+        assert_eq!(response.text(), Some("3"));
+
+        let requests = server.requests();
+        let error = last_message(&requests[1])["content"].as_str().unwrap();
+        assert!(
+            error.starts_with("Error: the arguments do not match the parameters of `add`: `a`:"),
+            "{error}"
+        );
+        assert_eq!(last_message(&requests[2])["content"], "3");
+    }
+
+    #[tokio::test]
+    async fn malformed_arguments_fail_once_the_retries_are_used_up() {
+        let server = MockServer::start(vec![
+            tool_call("call_0", "add", r#"{"a":"one","b":2}"#),
+            tool_call("call_1", "add", r#"{"a":"one","b":"two"}"#),
+            answer("3", "stop"),
+        ])
+        .await;
+        let mut client = server.client();
+        client.tools = vec![ADD];
+        client.max_argument_retries = 1;
+
+        let Err(Error::ToolArguments { name, source }) = client.chat("Add one and two").await
+        else {
+            panic!("expected the malformed arguments to fail the call");
+        };
+        assert_eq!(name, "add");
+        assert_eq!(source.path, "a");
+        assert!(client.context.is_empty());
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn chunk_stream_is_metered() {
+        let server = MockServer::start(vec![answer("Hi", "stop")]).await;
+        let mut client = server.client();
+        client.context.push(Message::user("Hello"));
+        client.budget = Budget {
+            max_tokens: Some(15),
+            max_cost: None,
+        };
+
+        let mut chunks = client.chunk_stream(ChatOptions::default());
+        while let Some(chunk) = next(&mut chunks).await {
+            chunk.unwrap();
+        }
+        drop(chunks);
+        assert_eq!(client.usage.total.total_tokens, 15);
+        assert_eq!(client.usage.by_model[&Model::DeepSeekChat].total_tokens, 15);
+
+        let mut chunks = client.chunk_stream(ChatOptions::default());
+        let Some(Err(Error::BudgetExceeded { limit, transcript })) = next(&mut chunks).await else {
+            panic!("expected the budget to stop the request");
+        };
+        assert_eq!(limit, BudgetLimit::Tokens(15));
+        assert!(transcript.is_empty());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejected_requests_are_sent_again() {
+        let server = MockServer::start(vec![
+            Response::Error {
+                status: 429,
+                retry_after: Some(1),
+            },
+            Response::Error {
+                status: 503,
+                retry_after: None,
+            },
+            answer("Hi", "stop"),
+        ])
+        .await;
+        let mut client = server.client();
+        let retries = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = std::sync::Arc::clone(&retries);
+        let mut retry = RetryPolicy::default();
+        retry.max_attempts = 3;
+        retry.base_delay = std::time::Duration::from_millis(10);
+        retry.jitter = false;
+        client.retry = retry.on_retry(move |retry| {
+            let status = match retry.error {
+                Error::Api(err) => err.status(),
+                err => panic!("unexpected error: {err:?}"),
+            };
+            recorded
+                .lock()
+                .unwrap()
+                .push((retry.attempt, retry.delay, status));
+        });
+
+        let started = std::time::Instant::now();
+        let response = client.chat("Hello").await.unwrap();
+        assert_eq!(response.text(), Some("Hi"));
+        assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+        assert_eq!(server.requests().len(), 3);
+        assert_eq!(
+            *retries.lock().unwrap(),
+            vec![
+                (
+                    1,
+                    std::time::Duration::from_secs(1),
+                    reqwest::StatusCode::TOO_MANY_REQUESTS
+                ),
+                (
+                    2,
+                    std::time::Duration::from_millis(20),
+                    reqwest::StatusCode::SERVICE_UNAVAILABLE
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_not_sent_again() {
+        let server = MockServer::start(vec![
+            Response::Error {
+                status: 500,
+                retry_after: None,
+            },
+            answer("Hi", "stop"),
+        ])
+        .await;
+        let mut client = server.client();
+        client.retry = RetryPolicy::default();
+
+        assert!(matches!(
+            client.chat("Hello").await,
+            Err(Error::Api(crate::ApiError::ServerError(_)))
+        ));
+        assert_eq!(server.requests().len(), 1);
+        assert!(client.context.is_empty());
+    }
+
+    #[tokio::test]
+    async fn options_override_the_client_for_one_call() {
+        let server = MockServer::start(vec![answer("Hi", "stop"), answer("Hi", "stop")]).await;
+        let mut client = server.client();
+        let options = ChatOptions {
+            model: Some(Model::DeepSeekReasoner),
+            temperature: Some(0.25),
+            max_tokens: Some(100),
+            stop: Some(vec!["END".to_string()]),
+            ..ChatOptions::default()
+        };
+
+        client.chat_with("Hello", options).await.unwrap();
+        assert_eq!(client.model, Model::DeepSeekChat);
+        assert_eq!(client.temperature, 1.0);
+        assert!(client.stop.is_empty());
+        assert_eq!(client.max_tokens, None);
+        assert_eq!(
+            client.usage.by_model[&Model::DeepSeekReasoner].total_tokens,
+            15
+        );
+
+        client.chat("Hello again").await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].body["model"], "deepseek-reasoner");
+        assert_eq!(requests[0].body["temperature"], 0.25);
+        assert_eq!(requests[0].body["max_tokens"], 100);
+        assert_eq!(requests[0].body["stop"], json!(["END"]));
+
+        assert_eq!(requests[1].body["model"], "deepseek-chat");
+        assert_eq!(requests[1].body["temperature"], 1.0);
+        assert_eq!(requests[1].body["max_tokens"], json!(null));
+        assert_eq!(requests[1].body.get("stop"), None);
     }
 }
@@ -1,13 +1,133 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
 use crate::api;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Model {
     #[serde(rename = "deepseek-chat")]
     DeepSeekChat,
     #[serde(rename = "deepseek-reasoner")]
     DeepSeekReasoner,
+    /// A model ID this crate does not know, e.g. a newly released model or one served by another
+    /// OpenAI-compatible server.
+    ///
+    /// Use [`Model::from`] to turn an ID into a `Model`, so known IDs get their own variant.
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl Model {
+    /// The model ID used by the API.
+    #[must_use]
+    pub fn id(&self) -> &str {
+        match self {
+            Self::DeepSeekChat => "deepseek-chat",
+            Self::DeepSeekReasoner => "deepseek-reasoner",
+            Self::Other(id) => id,
+        }
+    }
+
+    /// The largest `max_tokens` the model accepts, or `None` if it is not known.
+    ///
+    /// See the [documentation](https://api-docs.deepseek.com/quick_start/pricing).
+    #[must_use]
+    pub fn max_output_tokens(&self) -> Option<u32> {
+        match self {
+            Self::DeepSeekChat => Some(8 * 1024),
+            Self::DeepSeekReasoner => Some(64 * 1024),
+            Self::Other(_) => None,
+        }
+    }
+}
+
+impl From<&str> for Model {
+    fn from(id: &str) -> Self {
+        match id {
+            "deepseek-chat" => Self::DeepSeekChat,
+            "deepseek-reasoner" => Self::DeepSeekReasoner,
+            _ => Self::Other(id.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+/// Whether and which tools the model calls.
+///
+/// A choice that forces a tool call only applies to the first request of a chat call, so the
+/// model can answer once it has the tool results.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ToolChoice {
+    /// The model decides whether to call tools.
+    #[default]
+    Auto,
+    /// The model does not call tools.
+    None,
+    /// The model calls one or more tools.
+    Required,
+    /// The model calls the tool with this name.
+    Function(String),
+}
+
+impl From<&ToolChoice> for api::request::ToolChoice {
+    fn from(value: &ToolChoice) -> Self {
+        use api::request::ToolChoiceMode;
+
+        match value {
+            ToolChoice::Auto => Self::Mode(ToolChoiceMode::Auto),
+            ToolChoice::None => Self::Mode(ToolChoiceMode::None),
+            ToolChoice::Required => Self::Mode(ToolChoiceMode::Required),
+            ToolChoice::Function(name) => Self::Function {
+                r#type: api::ToolCallType::Function,
+                function: api::request::FunctionName { name: name.clone() },
+            },
+        }
+    }
+}
+
+/// What a chat call does once the model has used up its round-trips, see
+/// [`Client::max_round_trips`](crate::Client::max_round_trips).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundTripsExhausted {
+    /// Fail with [`Error::TooManyRoundTrips`](crate::Error::TooManyRoundTrips).
+    #[default]
+    Error,
+    /// Send one more request with all tools disabled, so the model has to answer. Tool calls it
+    /// makes anyway are dropped from its answer.
+    ForceAnswer,
+    /// Stop without an answer and return the messages produced so far.
+    ReturnPartial,
+}
+
+/// What happens to the context when a chat call does not complete, e.g. because its stream was
+/// dropped or cancelled, or a request failed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CancelPolicy {
+    /// Remove the user message and everything added after it.
+    #[default]
+    Rollback,
+    /// Keep the messages received so far. The answer being streamed is committed with
+    /// [`Assistant::truncated`](crate::message::Assistant::truncated) set, and unanswered tool
+    /// calls get a result saying they were cancelled. If nothing was received yet, the turn is
+    /// rolled back instead.
+    CommitPartial,
+}
+
+/// How the API key is attached to requests.
+#[derive(Clone, Debug)]
+pub enum AuthScheme {
+    /// `Authorization: Bearer <api_key>`, as expected by DeepSeek and OpenAI-compatible servers.
+    Bearer,
+    /// The raw API key in the given header, e.g. `api-key: <api_key>`.
+    Header(reqwest::header::HeaderName),
+    /// No authentication, e.g. for a local server or a gateway that injects credentials itself.
+    None,
 }
 
 #[derive(Clone)]
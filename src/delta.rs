@@ -1,4 +1,4 @@
-use crate::Role;
+use crate::{Role, RoundTrip, Usage};
 
 #[derive(Debug, Clone)]
 pub enum Delta {
@@ -19,4 +19,8 @@ pub enum Delta {
         tool_call_id: String,
         content: String,
     },
+    /// Token usage of a model round-trip, emitted once the round-trip's response is complete.
+    Usage(Usage),
+    /// The metadata of a round-trip, emitted once its response is complete.
+    Finished(RoundTrip),
 }
@@ -0,0 +1,270 @@
+use std::fmt;
+
+use reqwest::StatusCode;
+
+use crate::{
+    ArgumentsError, BudgetLimit,
+    api::response::{ErrorDetail, ErrorResponse},
+    message::Message,
+};
+
+/// Errors returned by [`Client`](crate::Client) methods and streams.
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or the response body could not be read.
+    Transport(reqwest::Error),
+
+    /// The API responded with one of its documented error status codes.
+    Api(ApiError),
+
+    /// The API responded with an undocumented status code or an unrecognized error body.
+    Status {
+        status: StatusCode,
+        /// The raw response body.
+        body: String,
+    },
+
+    /// The response body is not valid JSON of the expected shape.
+    Decode(serde_json::Error),
+
+    /// The model called a tool that is not registered on the client.
+    UnknownTool { name: String },
+
+    /// The arguments generated by the model could not be parsed for the tool, more often than
+    /// [`Client::max_argument_retries`](crate::Client::max_argument_retries) allows.
+    ToolArguments {
+        name: String,
+        source: ArgumentsError,
+    },
+
+    /// The response violated the expected protocol, e.g. a malformed SSE stream.
+    Protocol(String),
+
+    /// A [`Budget`](crate::Budget) limit was reached, so no further request was sent.
+    BudgetExceeded {
+        limit: BudgetLimit,
+        /// The messages added to the context during the call before it was aborted, empty for
+        /// calls that do not use the context.
+        transcript: Vec<Message>,
+    },
+
+    /// The model kept calling tools beyond the allowed number of round-trips.
+    TooManyRoundTrips {
+        max: u32,
+        /// The messages added to the context during the call before it was aborted.
+        transcript: Vec<Message>,
+    },
+
+    /// A request parameter is outside the range accepted by the API.
+    InvalidParameter { name: &'static str, reason: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(err) => write!(f, "transport error: {err}"),
+            Self::Api(err) => err.fmt(f),
+            Self::Status { status, body } => write!(f, "HTTP {status}: {body}"),
+            Self::Decode(err) => write!(f, "failed to decode response: {err}"),
+            Self::UnknownTool { name } => write!(f, "model called unknown tool `{name}`"),
+            Self::ToolArguments { name, source } => {
+                write!(f, "invalid arguments for tool `{name}`: {source}")
+            }
+            Self::Protocol(msg) => write!(f, "protocol error: {msg}"),
+            Self::BudgetExceeded { limit, .. } => match limit {
+                BudgetLimit::Tokens(max) => write!(f, "token budget of {max} exceeded"),
+                BudgetLimit::Cost(max, currency) => {
+                    write!(f, "cost budget of {max} {currency:?} exceeded")
+                }
+            },
+            Self::TooManyRoundTrips { max, .. } => {
+                write!(f, "model did not answer within {max} round-trips")
+            }
+            Self::InvalidParameter { name, reason } => write!(f, "invalid `{name}`: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err),
+            Self::Api(err) => Some(err),
+            Self::Decode(err) => Some(err),
+            Self::ToolArguments { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        Self::Transport(value)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Decode(value)
+    }
+}
+
+impl Error {
+    /// Whether the request was rejected without being processed and may succeed if sent again.
+    ///
+    /// Other server errors such as `500` or `504` may arrive after the model has generated, and
+    /// billed, the answer, so sending the request again is not safe.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Transport(err) => err.is_connect(),
+            Self::Api(err) => matches!(
+                err,
+                ApiError::RateLimitReached(_) | ApiError::ServerOverloaded(_)
+            ),
+            Self::Status { status, .. } => matches!(
+                *status,
+                StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+            ),
+            _ => false,
+        }
+    }
+
+    /// Builds the error for a non-success response from its status code and body.
+    pub(crate) fn from_response(status: StatusCode, body: String) -> Self {
+        let detail = serde_json::from_str::<ErrorResponse>(&body).map(|resp| resp.error);
+        match (ApiError::kind_for(status), detail) {
+            (Some(kind), Ok(detail)) => Self::Api(kind(detail)),
+            _ => Self::Status { status, body },
+        }
+    }
+}
+
+/// An error returned by the DeepSeek API, one variant per documented status code.
+///
+/// See the [error codes documentation](https://api-docs.deepseek.com/quick_start/error_codes).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// `400`: The request body has an invalid format.
+    InvalidFormat(ErrorDetail),
+    /// `401`: Authentication failed due to a wrong API key.
+    AuthenticationFails(ErrorDetail),
+    /// `402`: The account has run out of balance.
+    InsufficientBalance(ErrorDetail),
+    /// `422`: The request contains invalid parameters.
+    InvalidParameters(ErrorDetail),
+    /// `429`: Requests are being sent too quickly.
+    RateLimitReached(ErrorDetail),
+    /// `500`: The server encountered an issue.
+    ServerError(ErrorDetail),
+    /// `503`: The server is overloaded due to high traffic.
+    ServerOverloaded(ErrorDetail),
+}
+
+impl ApiError {
+    fn kind_for(status: StatusCode) -> Option<fn(ErrorDetail) -> Self> {
+        Some(match status.as_u16() {
+            400 => Self::InvalidFormat,
+            401 => Self::AuthenticationFails,
+            402 => Self::InsufficientBalance,
+            422 => Self::InvalidParameters,
+            429 => Self::RateLimitReached,
+            500 => Self::ServerError,
+            503 => Self::ServerOverloaded,
+            _ => return None,
+        })
+    }
+
+    /// The HTTP status code this error was returned with.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::InvalidFormat(_) => StatusCode::BAD_REQUEST,
+            Self::AuthenticationFails(_) => StatusCode::UNAUTHORIZED,
+            Self::InsufficientBalance(_) => StatusCode::PAYMENT_REQUIRED,
+            Self::InvalidParameters(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::RateLimitReached(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::ServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::ServerOverloaded(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// The error body returned by the API.
+    pub fn detail(&self) -> &ErrorDetail {
+        match self {
+            Self::InvalidFormat(detail)
+            | Self::AuthenticationFails(detail)
+            | Self::InsufficientBalance(detail)
+            | Self::InvalidParameters(detail)
+            | Self::RateLimitReached(detail)
+            | Self::ServerError(detail)
+            | Self::ServerOverloaded(detail) => detail,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "API error {}: {}", self.status(), self.detail().message)
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documented_status_is_parsed_into_api_error() {
+        let body = r#"{"error":{"message":"Insufficient Balance","type":"unknown_error","param":null,"code":"invalid_request_error"}}"#;
+        let err = Error::from_response(StatusCode::PAYMENT_REQUIRED, body.to_string());
+
+        let Error::Api(ApiError::InsufficientBalance(detail)) = err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(detail.message, "Insufficient Balance");
+        assert_eq!(detail.r#type.as_deref(), Some("unknown_error"));
+        assert_eq!(detail.code.as_deref(), Some("invalid_request_error"));
+    }
+
+    #[test]
+    fn api_error_reports_its_status() {
+        let body = r#"{"error":{"message":"Rate limit reached","type":null,"code":null}}"#;
+        let err = Error::from_response(StatusCode::TOO_MANY_REQUESTS, body.to_string());
+
+        let Error::Api(err) = err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(err.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn unrecognized_body_falls_back_to_status() {
+        let err = Error::from_response(StatusCode::BAD_GATEWAY, "Bad Gateway".to_string());
+        assert!(matches!(err, Error::Status { status, .. } if status == StatusCode::BAD_GATEWAY));
+
+        let err = Error::from_response(StatusCode::UNAUTHORIZED, "<html></html>".to_string());
+        assert!(matches!(err, Error::Status { status, .. } if status == StatusCode::UNAUTHORIZED));
+    }
+
+    #[test]
+    fn only_unprocessed_requests_are_retryable() {
+        let body = r#"{"error":{"message":"Server overloaded","type":null,"code":null}}"#;
+        for status in [
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::SERVICE_UNAVAILABLE,
+        ] {
+            assert!(Error::from_response(status, body.to_string()).is_retryable());
+            assert!(Error::from_response(status, String::new()).is_retryable());
+        }
+
+        for status in [
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::BAD_GATEWAY,
+            StatusCode::GATEWAY_TIMEOUT,
+            StatusCode::BAD_REQUEST,
+        ] {
+            assert!(!Error::from_response(status, body.to_string()).is_retryable());
+            assert!(!Error::from_response(status, String::new()).is_retryable());
+        }
+    }
+}
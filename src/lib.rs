@@ -1,31 +1,56 @@
-#![feature(gen_blocks)]
-#![feature(async_iterator)]
+#![cfg_attr(feature = "nightly", feature(async_iterator))]
 
 extern crate self as deepseek_api;
 
 mod api;
+mod builder;
 mod client;
 mod config;
 mod delta;
+mod error;
 pub mod message;
+#[cfg(test)]
+mod mock;
+mod options;
+mod pricing;
+mod response;
+mod retry;
 mod stream;
 mod tool;
+mod turn;
 
 use serde::{Deserialize, Serialize};
 
-pub use api::response::FinishReason;
+pub use api::ToolCallType;
+pub use api::response::{
+    BalanceInfo, CompletionTokensDetails, Currency, ErrorDetail, FinishReason, ModelInfo, Usage,
+    UserBalance,
+};
+pub use api::response::{completion, streaming};
+pub use builder::ClientBuilder;
 pub use client::Client;
-pub use config::{Model, ResponseFormat};
+pub use config::{
+    AuthScheme, CancelPolicy, Model, ResponseFormat, RoundTripsExhausted, ToolChoice,
+};
 pub use deepseek_api_macros::tool;
 pub use delta::Delta;
+pub use error::{ApiError, Error};
+pub use options::{ChatOptions, FimOptions};
+pub use pricing::{Budget, BudgetLimit, PriceTable, TokenPrices, UsageTotals};
+pub use response::{ChatResponse, RoundTrip};
+pub use retry::{RetryAttempt, RetryPolicy};
+#[cfg(feature = "nightly")]
 pub use stream::AsyncIteratorNext;
-pub use tool::{Tool, ToolFuture};
+pub use stream::{CancelHandle, ChatStream};
+pub use tool::{ArgumentsError, Tool, ToolFuture};
 
 #[doc(hidden)]
 pub mod __private {
     pub use schemars;
     pub use serde;
     pub use serde_json;
+
+    pub use crate::tool::{parameters_schema, parse_arguments, serialize_result};
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
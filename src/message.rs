@@ -65,6 +65,10 @@ pub struct Assistant {
     pub content: String,
     pub reasoning_content: Option<String>,
     pub tool_calls: Option<Vec<ToolCall>>,
+    /// Whether the answer was cut off because its turn was cancelled, see
+    /// [`CancelPolicy::CommitPartial`](crate::CancelPolicy::CommitPartial).
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 impl From<Assistant> for Message {
@@ -116,6 +120,7 @@ impl From<&Message> for api::request::Message {
                     .tool_calls
                     .as_ref()
                     .map(|tcs| tcs.iter().map(api::request::ToolCall::from).collect()),
+                prefix: false,
             },
             Message::Tool(t) => api::request::Message::Tool {
                 tool_call_id: t.tool_call_id.clone(),
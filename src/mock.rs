@@ -0,0 +1,229 @@
+//! A minimal HTTP server that replays canned streaming responses, for testing the chat loop end
+//! to end.
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Value, json};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{Client, Model, RetryPolicy};
+
+/// A canned response of a [`MockServer`].
+pub(crate) enum Response {
+    /// A complete event stream.
+    Events(String),
+    /// The start of an event stream, after which the connection is kept open without sending
+    /// anything else.
+    Stalled(String),
+    /// An error with the given status code and, if set, a `Retry-After` header in seconds.
+    Error {
+        status: u16,
+        retry_after: Option<u64>,
+    },
+}
+
+/// A request received by a [`MockServer`].
+#[derive(Debug)]
+pub(crate) struct Request {
+    pub path: String,
+    pub body: Value,
+}
+
+pub(crate) struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Starts a server that answers the n-th request with the n-th of `responses`. Requests
+    /// beyond that are rejected with `400`.
+    pub async fn start(responses: Vec<Response>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let received = Arc::clone(&requests);
+        tokio::spawn(async move {
+            let mut responses = responses.into_iter();
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let request = read_request(&mut socket).await;
+                received.lock().unwrap().push(request);
+
+                let response = match responses.next() {
+                    Some(Response::Events(body)) => {
+                        http_response(200, "", "text/event-stream", &body)
+                    }
+                    Some(Response::Stalled(body)) => {
+                        // Without a length, the body only ends when the connection is closed.
+                        let head = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\n";
+                        socket.write_all(head.as_bytes()).await.unwrap();
+                        socket.write_all(body.as_bytes()).await.unwrap();
+                        tokio::spawn(async move {
+                            let _socket = socket;
+                            std::future::pending::<()>().await;
+                        });
+                        continue;
+                    }
+                    Some(Response::Error {
+                        status,
+                        retry_after,
+                    }) => error_response(status, retry_after, "Canned error"),
+                    None => error_response(400, None, "No canned response left"),
+                };
+                socket.write_all(response.as_bytes()).await.unwrap();
+                socket.shutdown().await.ok();
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// A client that sends its requests to this server without retrying.
+    pub fn client(&self) -> Client {
+        let mut client = Client::new(Model::DeepSeekChat, "sk-test");
+        client.base_url = self.url.clone();
+        client.retry = RetryPolicy::none();
+        client
+    }
+
+    /// The requests received so far.
+    pub fn requests(&self) -> Vec<Request> {
+        std::mem::take(&mut *self.requests.lock().unwrap())
+    }
+}
+
+/// A complete response; `headers` are extra header lines, each ending with `\r\n`.
+fn http_response(status: u16, headers: &str, content_type: &str, body: &str) -> String {
+    let status = reqwest::StatusCode::from_u16(status).unwrap();
+    format!(
+        "HTTP/1.1 {status}\r\n{headers}content-type: {content_type}\r\n\
+         content-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+fn error_response(status: u16, retry_after: Option<u64>, message: &str) -> String {
+    let headers = retry_after.map_or_else(String::new, |secs| format!("retry-after: {secs}\r\n"));
+    let body = json!({ "error": { "message": message, "type": null, "code": null } });
+    http_response(status, &headers, "application/json", &body.to_string())
+}
+
+async fn read_request(socket: &mut TcpStream) -> Request {
+    let mut buf = Vec::new();
+    let header_end = loop {
+        if let Some(end) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+        let mut chunk = [0; 4096];
+        let n = socket.read(&mut chunk).await.unwrap();
+        assert_ne!(n, 0, "connection closed before the request was complete");
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).into_owned();
+    let path = head
+        .split_whitespace()
+        .nth(1)
+        .expect("request line has a path")
+        .to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .map_or(0, |(_, value)| value.trim().parse().unwrap());
+
+    while buf.len() < header_end + content_length {
+        let mut chunk = [0; 4096];
+        let n = socket.read(&mut chunk).await.unwrap();
+        assert_ne!(n, 0, "connection closed before the body was complete");
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let body = &buf[header_end..header_end + content_length];
+    Request {
+        path,
+        body: serde_json::from_slice(body).unwrap_or(Value::Null),
+    }
+}
+
+/// Encodes `deltas` as the event stream of one round-trip, finishing with `finish_reason` and
+/// the usage, or stalling after the deltas if there is none.
+fn events(deltas: &[Value], finish_reason: Option<&str>) -> Response {
+    let chunk = |delta: &Value, finish_reason: Option<&str>| {
+        json!({
+            "id": "930c60df-bf64-41c9-a88e-3ec75f81e00e",
+            "object": "chat.completion.chunk",
+            "created": 1_738_000_000,
+            "model": "deepseek-chat",
+            "system_fingerprint": "fp_3a5770e1b4_prod0225",
+            "choices": [{
+                "index": 0,
+                "delta": delta,
+                "logprobs": null,
+                "finish_reason": finish_reason,
+            }],
+        })
+    };
+
+    let mut chunks = vec![chunk(&json!({ "role": "assistant", "content": "" }), None)];
+    chunks.extend(deltas.iter().map(|delta| chunk(delta, None)));
+    let Some(finish_reason) = finish_reason else {
+        return Response::Stalled(encode(&chunks));
+    };
+    let mut last = chunk(&json!({ "content": "" }), Some(finish_reason));
+    last["usage"] = json!({
+        "prompt_tokens": 10,
+        "completion_tokens": 5,
+        "total_tokens": 15,
+        "prompt_cache_hit_tokens": 0,
+        "prompt_cache_miss_tokens": 10,
+    });
+    chunks.push(last);
+
+    let mut body = encode(&chunks);
+    body.push_str("data: [DONE]\n\n");
+    Response::Events(body)
+}
+
+fn encode(chunks: &[Value]) -> String {
+    chunks
+        .iter()
+        .map(|chunk| format!("data: {chunk}\n\n"))
+        .collect()
+}
+
+/// A round-trip in which the model answers `content`.
+pub(crate) fn answer(content: &str, finish_reason: &str) -> Response {
+    events(&[json!({ "content": content })], Some(finish_reason))
+}
+
+/// A round-trip in which the model starts answering with `content` and then stalls.
+pub(crate) fn stalled_answer(content: &str) -> Response {
+    events(&[json!({ "content": content })], None)
+}
+
+/// A round-trip in which the model calls the tool `name` with `arguments`.
+pub(crate) fn tool_call(id: &str, name: &str, arguments: &str) -> Response {
+    let call = |id: Option<&str>, name: Option<&str>, arguments: &str| {
+        json!({
+            "tool_calls": [{
+                "index": 0,
+                "id": id,
+                "type": id.map(|_| "function"),
+                "function": { "name": name, "arguments": arguments },
+            }],
+        })
+    };
+    let (head, tail) = arguments.split_at(arguments.len() / 2);
+    events(
+        &[
+            call(Some(id), Some(name), ""),
+            call(None, None, head),
+            call(None, None, tail),
+        ],
+        Some("tool_calls"),
+    )
+}
@@ -0,0 +1,113 @@
+use crate::{Budget, CancelPolicy, Model, ResponseFormat, RoundTripsExhausted, Tool, ToolChoice};
+
+/// Overrides of the [`Client`](crate::Client) settings for a single
+/// [`chat_with`](crate::Client::chat_with) or
+/// [`chat_stream`](crate::Client::chat_stream) call.
+///
+/// Fields left as `None` fall back to the client's value.
+///
+/// ```no_run
+/// # use deepseek_api::{ChatOptions, Client, Model, ResponseFormat};
+/// # async fn run(client: &mut Client) -> Result<(), deepseek_api::Error> {
+/// let options = ChatOptions {
+///     temperature: Some(0.0),
+///     response_format: Some(ResponseFormat::JsonObject),
+///     ..ChatOptions::default()
+/// };
+/// client.chat_with("Extract the dates as JSON.", options).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ChatOptions {
+    pub model: Option<Model>,
+    pub frequency_penalty: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub presence_penalty: Option<f32>,
+    pub response_format: Option<ResponseFormat>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+
+    /// The stop sequences, replacing [`Client::stop`](crate::Client::stop).
+    pub stop: Option<Vec<String>>,
+
+    /// The tools available to the model, replacing [`Client::tools`](crate::Client::tools).
+    pub tools: Option<Vec<Tool>>,
+
+    /// Replaces [`Client::tool_choice`](crate::Client::tool_choice). A named tool must be among
+    /// the tools available to this call.
+    pub tool_choice: Option<ToolChoice>,
+
+    /// A limit on the usage of this call, enforced in addition to [`Client::budget`](crate::Client::budget).
+    pub budget: Option<Budget>,
+
+    /// Replaces [`Client::max_round_trips`](crate::Client::max_round_trips).
+    pub max_round_trips: Option<u32>,
+
+    /// Replaces [`Client::on_round_trips_exhausted`](crate::Client::on_round_trips_exhausted).
+    pub on_round_trips_exhausted: Option<RoundTripsExhausted>,
+
+    /// Text the model's answer must start with. The model continues it instead of starting a
+    /// new answer.
+    ///
+    /// The prefix is part of the assistant message added to the context, but it is not streamed
+    /// as a [`Delta`](crate::Delta). Uses the beta
+    /// [chat prefix completion](https://api-docs.deepseek.com/guides/chat_prefix_completion),
+    /// so [`Client::base_url`](crate::Client::base_url) must also serve `/beta/chat/completions`.
+    pub prefix: Option<String>,
+
+    /// Replaces [`Client::max_continuations`](crate::Client::max_continuations).
+    pub max_continuations: Option<u32>,
+
+    /// Replaces [`Client::max_argument_retries`](crate::Client::max_argument_retries).
+    pub max_argument_retries: Option<u32>,
+
+    /// Replaces [`Client::on_cancel`](crate::Client::on_cancel).
+    pub on_cancel: Option<CancelPolicy>,
+}
+
+/// Parameters of a [`fim_completion`](crate::Client::fim_completion) call.
+///
+/// The sampling parameters left as `None` fall back to the client's value, the others are not
+/// sent so the API defaults apply.
+///
+/// ```no_run
+/// # use deepseek_api::{Client, FimOptions, Model};
+/// # async fn run(client: &mut Client) -> Result<(), deepseek_api::Error> {
+/// let options = FimOptions {
+///     max_tokens: Some(128),
+///     stop: Some(vec!["\n\n".to_string()]),
+///     ..FimOptions::default()
+/// };
+/// let completion = client
+///     .fim_completion("def add(a, b):\n", "\n\nprint(add(1, 2))", options)
+///     .await?;
+/// println!("{}", completion.text());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FimOptions {
+    /// Replaces [`Client::model`](crate::Client::model). Only
+    /// [`Model::DeepSeekChat`] supports FIM completion.
+    pub model: Option<Model>,
+
+    /// Echo back the prompt in addition to the completion.
+    pub echo: bool,
+
+    /// Include the log probabilities of this many most likely tokens, at most
+    /// [`Client::MAX_LOGPROBS`](crate::Client::MAX_LOGPROBS).
+    pub logprobs: Option<u32>,
+
+    /// At most [`Client::MAX_FIM_TOKENS`](crate::Client::MAX_FIM_TOKENS).
+    pub max_tokens: Option<u32>,
+
+    /// Up to [`Client::MAX_STOP_SEQUENCES`](crate::Client::MAX_STOP_SEQUENCES) sequences where
+    /// the API will stop generating further tokens.
+    pub stop: Option<Vec<String>>,
+
+    pub frequency_penalty: Option<f32>,
+    pub presence_penalty: Option<f32>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use crate::{Model, Usage, api::response::Currency};
+
+/// Prices per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenPrices {
+    /// Price of input tokens that hit the context cache.
+    pub cache_hit_input: f64,
+    /// Price of input tokens that missed the context cache.
+    pub cache_miss_input: f64,
+    /// Price of output tokens, including reasoning tokens.
+    pub output: f64,
+}
+
+impl TokenPrices {
+    /// The cost of `usage` at these prices.
+    #[must_use]
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        (usage.prompt_cache_hit_tokens as f64 * self.cache_hit_input
+            + usage.prompt_cache_miss_tokens as f64 * self.cache_miss_input
+            + usage.completion_tokens as f64 * self.output)
+            / 1_000_000.0
+    }
+}
+
+/// Token prices per model and currency, used to estimate the cost of requests.
+///
+/// The default table holds the [published prices](https://api-docs.deepseek.com/quick_start/pricing)
+/// of DeepSeek's models. Use [`PriceTable::set`] to update them or add prices for other models.
+#[derive(Debug, Clone)]
+pub struct PriceTable {
+    prices: HashMap<(Model, Currency), TokenPrices>,
+}
+
+impl PriceTable {
+    /// A table without any prices.
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            prices: HashMap::new(),
+        }
+    }
+
+    pub fn set(&mut self, model: Model, currency: Currency, prices: TokenPrices) {
+        self.prices.insert((model, currency), prices);
+    }
+
+    #[must_use]
+    pub fn get(&self, model: &Model, currency: Currency) -> Option<&TokenPrices> {
+        self.prices.get(&(model.clone(), currency))
+    }
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        let usd = TokenPrices {
+            cache_hit_input: 0.028,
+            cache_miss_input: 0.28,
+            output: 0.42,
+        };
+        let cny = TokenPrices {
+            cache_hit_input: 0.2,
+            cache_miss_input: 2.0,
+            output: 3.0,
+        };
+
+        let mut table = Self::empty();
+        for model in [Model::DeepSeekChat, Model::DeepSeekReasoner] {
+            table.set(model.clone(), Currency::Usd, usd);
+            table.set(model, Currency::Cny, cny);
+        }
+        table
+    }
+}
+
+/// Token usage accumulated over many requests, broken down by model.
+#[derive(Debug, Clone, Default)]
+pub struct UsageTotals {
+    /// The usage of all requests combined.
+    pub total: Usage,
+    /// The usage of the requests made with each model.
+    pub by_model: HashMap<Model, Usage>,
+}
+
+impl UsageTotals {
+    pub fn record(&mut self, model: &Model, usage: &Usage) {
+        self.total += usage;
+        *self.by_model.entry(model.clone()).or_default() += usage;
+    }
+
+    /// The estimated cost of the recorded usage, or `None` if the table has no price in
+    /// `currency` for one of the models used.
+    #[must_use]
+    pub fn cost(&self, prices: &PriceTable, currency: Currency) -> Option<f64> {
+        self.by_model
+            .iter()
+            .map(|(model, usage)| Some(prices.get(model, currency)?.cost(usage)))
+            .sum()
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Limits on the usage of a [`Client`](crate::Client) or of a single call.
+///
+/// Once a limit is reached, the client stops sending requests and returns
+/// [`Error::BudgetExceeded`](crate::Error::BudgetExceeded).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Budget {
+    /// The maximum number of tokens, prompt and completion combined.
+    pub max_tokens: Option<u64>,
+    /// The maximum estimated cost. It counts as reached once a model without a price in the
+    /// given currency has been used, since its cost cannot be estimated.
+    pub max_cost: Option<(f64, Currency)>,
+}
+
+/// The limit of a [`Budget`] that was reached.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BudgetLimit {
+    Tokens(u64),
+    Cost(f64, Currency),
+}
+
+impl Budget {
+    /// A budget without any limits.
+    #[must_use]
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Returns the first limit that `usage` has reached.
+    #[must_use]
+    pub fn reached(&self, usage: &UsageTotals, prices: &PriceTable) -> Option<BudgetLimit> {
+        if let Some(max_tokens) = self.max_tokens
+            && usage.total.total_tokens >= max_tokens
+        {
+            return Some(BudgetLimit::Tokens(max_tokens));
+        }
+
+        if let Some((max_cost, currency)) = self.max_cost {
+            let cost = usage.cost(prices, currency);
+            if cost.is_none_or(|cost| cost >= max_cost) {
+                return Some(BudgetLimit::Cost(max_cost, currency));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(hit: u64, miss: u64, completion: u64) -> Usage {
+        Usage {
+            completion_tokens: completion,
+            prompt_tokens: hit + miss,
+            prompt_cache_hit_tokens: hit,
+            prompt_cache_miss_tokens: miss,
+            total_tokens: hit + miss + completion,
+            completion_tokens_details: None,
+        }
+    }
+
+    #[test]
+    fn totals_accumulate_per_model() {
+        let mut totals = UsageTotals::default();
+        totals.record(&Model::DeepSeekChat, &usage(10, 20, 30));
+        totals.record(&Model::DeepSeekChat, &usage(1, 2, 3));
+        totals.record(&Model::DeepSeekReasoner, &usage(100, 0, 0));
+
+        assert_eq!(totals.total, usage(111, 22, 33));
+        assert_eq!(totals.by_model[&Model::DeepSeekChat], usage(11, 22, 33));
+    }
+
+    #[test]
+    fn cost_uses_the_price_of_each_model() {
+        let mut prices = PriceTable::empty();
+        let unit = |price| TokenPrices {
+            cache_hit_input: price,
+            cache_miss_input: 2.0 * price,
+            output: 4.0 * price,
+        };
+        prices.set(Model::DeepSeekChat, Currency::Usd, unit(1.0));
+        prices.set(Model::DeepSeekReasoner, Currency::Usd, unit(10.0));
+
+        let mut totals = UsageTotals::default();
+        totals.record(
+            &Model::DeepSeekChat,
+            &usage(1_000_000, 1_000_000, 1_000_000),
+        );
+        totals.record(&Model::DeepSeekReasoner, &usage(0, 0, 500_000));
+
+        assert_eq!(
+            totals.cost(&prices, Currency::Usd),
+            Some(1.0 + 2.0 + 4.0 + 20.0)
+        );
+        assert_eq!(totals.cost(&prices, Currency::Cny), None);
+    }
+
+    #[test]
+    fn budget_reports_the_reached_limit() {
+        let mut totals = UsageTotals::default();
+        totals.record(&Model::DeepSeekChat, &usage(0, 1_000_000, 1_000_000));
+        let prices = PriceTable::default();
+
+        let budget = Budget {
+            max_tokens: Some(3_000_000),
+            max_cost: Some((1.0, Currency::Usd)),
+        };
+        assert_eq!(budget.reached(&totals, &prices), None);
+
+        let budget = Budget {
+            max_tokens: Some(2_000_000),
+            ..budget
+        };
+        assert_eq!(
+            budget.reached(&totals, &prices),
+            Some(BudgetLimit::Tokens(2_000_000))
+        );
+
+        let budget = Budget {
+            max_tokens: None,
+            max_cost: Some((0.5, Currency::Usd)),
+        };
+        assert_eq!(
+            budget.reached(&totals, &prices),
+            Some(BudgetLimit::Cost(0.5, Currency::Usd))
+        );
+        assert_eq!(Budget::unlimited().reached(&totals, &prices), None);
+    }
+
+    #[test]
+    fn cost_limit_is_reached_when_a_price_is_missing() {
+        let mut totals = UsageTotals::default();
+        let prices = PriceTable::default();
+        let budget = Budget {
+            max_tokens: None,
+            max_cost: Some((1.0, Currency::Usd)),
+        };
+        assert_eq!(budget.reached(&totals, &prices), None);
+
+        totals.record(&Model::Other("deepseek-v4".to_string()), &usage(0, 1, 1));
+        assert_eq!(
+            budget.reached(&totals, &prices),
+            Some(BudgetLimit::Cost(1.0, Currency::Usd))
+        );
+    }
+}
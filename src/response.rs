@@ -0,0 +1,122 @@
+use crate::{FinishReason, Usage, message::Message};
+
+/// The result of a chat call, see [`Client::chat`](crate::Client::chat).
+#[derive(Debug, Clone)]
+pub struct ChatResponse {
+    /// The messages added to the context after the user message: the assistant's answers and
+    /// the results of the tools it called.
+    pub messages: Vec<Message>,
+
+    /// One entry per request sent, in order.
+    pub round_trips: Vec<RoundTrip>,
+
+    /// The token usage summed over all round-trips.
+    pub usage: Usage,
+}
+
+impl ChatResponse {
+    /// Why the model stopped generating in the last round-trip, or `None` if no request was
+    /// sent.
+    ///
+    /// [`FinishReason::Length`] means the answer was cut off by `max_tokens` or the context
+    /// length.
+    pub fn finish_reason(&self) -> Option<FinishReason> {
+        self.round_trips
+            .last()
+            .map(|round_trip| round_trip.finish_reason)
+    }
+
+    /// Whether the model finished its answer, as opposed to it being cut off or the call
+    /// returning before the model answered.
+    pub fn is_complete(&self) -> bool {
+        self.finish_reason() == Some(FinishReason::Stop)
+    }
+
+    /// The content of the last assistant message, i.e. the model's final answer.
+    pub fn text(&self) -> Option<&str> {
+        self.messages.iter().rev().find_map(|msg| match msg {
+            Message::Assistant(assistant) => Some(assistant.content.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// The metadata of a single response from the API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundTrip {
+    /// The completion ID assigned by the API, useful when contacting DeepSeek support.
+    pub id: String,
+
+    /// When the completion was created, as a Unix timestamp in seconds.
+    pub created: u64,
+
+    /// The backend configuration the model ran with.
+    pub system_fingerprint: String,
+
+    pub finish_reason: FinishReason,
+
+    pub usage: Usage,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message;
+
+    fn round_trip(finish_reason: FinishReason) -> RoundTrip {
+        RoundTrip {
+            id: "930c60df-bf64-41c9-a88e-3ec75f81e00e".to_string(),
+            created: 1_738_000_000,
+            system_fingerprint: "fp_3a5770e1b4".to_string(),
+            finish_reason,
+            usage: Usage::default(),
+        }
+    }
+
+    fn assistant(content: &str) -> Message {
+        message::Assistant {
+            name: None,
+            content: content.to_string(),
+            reasoning_content: None,
+            tool_calls: None,
+            truncated: false,
+        }
+        .into()
+    }
+
+    #[test]
+    fn accessors_read_the_last_round_trip() {
+        let response = ChatResponse {
+            messages: vec![
+                assistant(""),
+                message::Tool {
+                    tool_call_id: "call_0".to_string(),
+                    content: "3".to_string(),
+                }
+                .into(),
+                assistant("1 + 2 = 3"),
+            ],
+            round_trips: vec![
+                round_trip(FinishReason::ToolCalls),
+                round_trip(FinishReason::Length),
+            ],
+            usage: Usage::default(),
+        };
+
+        assert_eq!(response.text(), Some("1 + 2 = 3"));
+        assert_eq!(response.finish_reason(), Some(FinishReason::Length));
+        assert!(!response.is_complete());
+    }
+
+    #[test]
+    fn empty_response_has_no_text() {
+        let response = ChatResponse {
+            messages: Vec::new(),
+            round_trips: Vec::new(),
+            usage: Usage::default(),
+        };
+
+        assert_eq!(response.text(), None);
+        assert_eq!(response.finish_reason(), None);
+    }
+}
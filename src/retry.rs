@@ -0,0 +1,230 @@
+use std::{
+    hash::{BuildHasher, RandomState},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::Error;
+
+/// Controls how failed requests are retried.
+///
+/// Only failures where the request was rejected before being processed are retried: rate limits
+/// (`429`), server overload (`503`) and connection errors. Other server errors are not retried,
+/// since the request may have been processed, and billed, before they occurred.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one. `1` disables retrying.
+    pub max_attempts: u32,
+
+    /// The delay before the first retry. It doubles on every subsequent retry.
+    pub base_delay: Duration,
+
+    /// The upper bound of the delay between two attempts. If the server asks to wait longer via
+    /// `Retry-After`, the request is not retried.
+    pub max_delay: Duration,
+
+    /// Whether to randomize the delay, so that concurrent clients do not retry in lockstep.
+    pub jitter: bool,
+
+    /// Whether to wait for the duration of the `Retry-After` header instead, if the server sent one.
+    pub respect_retry_after: bool,
+
+    on_retry: Option<RetryHook>,
+}
+
+type RetryHook = Arc<dyn Fn(&RetryAttempt) + Send + Sync>;
+
+/// Describes a retry that is about to happen, see [`RetryPolicy::on_retry`].
+#[derive(Debug)]
+pub struct RetryAttempt<'a> {
+    /// The number of the failed attempt, starting from `1`.
+    pub attempt: u32,
+    /// How long the client waits before the next attempt.
+    pub delay: Duration,
+    /// The error of the failed attempt.
+    pub error: &'a Error,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+            respect_retry_after: true,
+            on_retry: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Sets a hook that is called before each retry.
+    #[must_use]
+    pub fn on_retry(mut self, hook: impl Fn(&RetryAttempt) + Send + Sync + 'static) -> Self {
+        self.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    /// Returns how long to wait before retrying the failed attempt, or `None` if it should not be
+    /// retried.
+    pub(crate) fn next_delay(
+        &self,
+        attempt: u32,
+        error: &Error,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts || !error.is_retryable() {
+            return None;
+        }
+
+        match retry_after {
+            Some(retry_after) if self.respect_retry_after => {
+                (retry_after <= self.max_delay).then_some(retry_after)
+            }
+            _ => {
+                let backoff = self
+                    .base_delay
+                    .saturating_mul(2u32.saturating_pow(attempt - 1));
+                let backoff = backoff.min(self.max_delay);
+                if self.jitter {
+                    // Equal jitter: keep half of the backoff and randomize the other half.
+                    Some(backoff / 2 + backoff.mul_f64(random_fraction() / 2.0))
+                } else {
+                    Some(backoff)
+                }
+            }
+        }
+    }
+
+    pub(crate) fn notify(&self, attempt: &RetryAttempt) {
+        if let Some(on_retry) = &self.on_retry {
+            on_retry(attempt);
+        }
+    }
+}
+
+/// Returns a pseudo-random number in `[0, 1)`.
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().hash_one(std::time::Instant::now());
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Parses the `Retry-After` header, which DeepSeek sends as a number of seconds.
+pub(crate) fn retry_after(resp: &reqwest::Response) -> Option<Duration> {
+    let value = resp.headers().get(reqwest::header::RETRY_AFTER)?;
+    let secs = value.to_str().ok()?.trim().parse::<u64>().ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retryable() -> Error {
+        Error::from_response(
+            reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            r#"{"error":{"message":"Server overloaded","type":null,"code":null}}"#.to_string(),
+        )
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(3),
+            jitter: false,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn delay_grows_exponentially_up_to_max() {
+        let policy = policy();
+        let error = retryable();
+        assert_eq!(
+            policy.next_delay(1, &error, None),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            policy.next_delay(2, &error, None),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            policy.next_delay(3, &error, None),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(policy.next_delay(4, &error, None), None);
+    }
+
+    #[test]
+    fn jitter_stays_within_backoff() {
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy()
+        };
+        let delay = policy.next_delay(2, &retryable(), None).unwrap();
+        assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_secs(2));
+    }
+
+    #[test]
+    fn retry_after_overrides_backoff() {
+        let policy = policy();
+        let error = retryable();
+        let retry_after = Some(Duration::from_secs(2));
+        assert_eq!(
+            policy.next_delay(1, &error, retry_after),
+            Some(Duration::from_secs(2))
+        );
+
+        let policy = RetryPolicy {
+            respect_retry_after: false,
+            ..policy
+        };
+        assert_eq!(
+            policy.next_delay(1, &error, retry_after),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn retry_after_beyond_max_delay_is_not_retried() {
+        let policy = policy();
+        let error = retryable();
+        assert_eq!(
+            policy.next_delay(1, &error, Some(Duration::from_secs(3))),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(
+            policy.next_delay(1, &error, Some(Duration::from_secs(60))),
+            None
+        );
+
+        let policy = RetryPolicy {
+            respect_retry_after: false,
+            ..policy
+        };
+        assert_eq!(
+            policy.next_delay(1, &error, Some(Duration::from_secs(60))),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn non_retryable_errors_are_not_retried() {
+        let error = Error::from_response(
+            reqwest::StatusCode::UNAUTHORIZED,
+            r#"{"error":{"message":"Authentication Fails","type":null,"code":null}}"#.to_string(),
+        );
+        assert_eq!(policy().next_delay(1, &error, None), None);
+    }
+}
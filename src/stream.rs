@@ -1,5 +1,28 @@
-use std::{async_iter::AsyncIterator, future::poll_fn, pin::Pin};
+#[cfg(feature = "nightly")]
+use std::async_iter::AsyncIterator;
+use std::{
+    fmt,
+    future::{Future, poll_fn},
+    pin::{Pin, pin},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll, Waker},
+};
 
+use async_stream::try_stream;
+use futures_core::Stream;
+use serde::de::DeserializeOwned;
+
+use crate::{Delta, Error};
+
+/// Resolves to the next item of `stream`.
+pub(crate) fn next<S: Stream + Unpin>(stream: &mut S) -> impl Future<Output = Option<S::Item>> {
+    poll_fn(move |cx| Pin::new(&mut *stream).poll_next(cx))
+}
+
+#[cfg(feature = "nightly")]
 pub trait AsyncIteratorNext: AsyncIterator {
     fn next(&mut self) -> impl std::future::Future<Output = Option<Self::Item>> + Send
     where
@@ -9,12 +32,599 @@ pub trait AsyncIteratorNext: AsyncIterator {
     }
 }
 
+#[cfg(feature = "nightly")]
 impl<T: AsyncIterator> AsyncIteratorNext for T {}
 
-pub(crate) fn parse_sse_data_frames(buf: &str) -> impl Iterator<Item = &str> {
-    buf.trim()
-        .split("\n\n")
-        .filter(|&frame| frame != "")
-        .map(|frame| frame.strip_prefix("data: ").unwrap())
-        .take_while(|data| *data != "[DONE]")
+/// Adapts a [`Stream`] into an [`AsyncIterator`].
+#[cfg(feature = "nightly")]
+pub(crate) struct IntoAsyncIterator<S>(Pin<Box<S>>);
+
+#[cfg(feature = "nightly")]
+impl<S> IntoAsyncIterator<S> {
+    pub fn new(stream: S) -> Self {
+        Self(Box::pin(stream))
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<S: Stream> AsyncIterator for IntoAsyncIterator<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.as_mut().poll_next(cx)
+    }
+}
+
+type BoxedDeltas<'a> = Pin<Box<dyn Stream<Item = Result<Delta, Error>> + Send + 'a>>;
+
+/// The stream returned by [`Client::chat_stream`](crate::Client::chat_stream).
+///
+/// Dropping it before it ends cancels the call, and the context is updated according to the
+/// call's [`CancelPolicy`](crate::CancelPolicy). A call can also be cancelled from another task
+/// through a [`CancelHandle`], after which the stream ends.
+pub struct ChatStream<'a> {
+    inner: Option<BoxedDeltas<'a>>,
+    cancel: CancelHandle,
+}
+
+impl<'a> ChatStream<'a> {
+    pub(crate) fn new(stream: impl Stream<Item = Result<Delta, Error>> + Send + 'a) -> Self {
+        Self {
+            inner: Some(Box::pin(stream)),
+            cancel: CancelHandle::default(),
+        }
+    }
+
+    /// Returns a handle that cancels this stream.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Cancels the call and applies its [`CancelPolicy`](crate::CancelPolicy) immediately.
+    pub fn cancel(&mut self) {
+        self.cancel.cancel();
+        self.inner = None;
+    }
+}
+
+impl Stream for ChatStream<'_> {
+    type Item = Result<Delta, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.cancel.register(cx.waker());
+        if self.cancel.is_cancelled() {
+            self.inner = None;
+        }
+
+        let Some(inner) = self.inner.as_mut() else {
+            return Poll::Ready(None);
+        };
+        let item = inner.as_mut().poll_next(cx);
+        if let Poll::Ready(None) = item {
+            self.inner = None;
+        }
+        item
+    }
+}
+
+impl fmt::Debug for ChatStream<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChatStream")
+            .field("finished", &self.inner.is_none())
+            .field("cancel", &self.cancel)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Cancels a [`ChatStream`], e.g. from another task or a UI event handler.
+///
+/// The stream ends the next time it is polled; a task waiting on it is woken up.
+#[derive(Clone, Default)]
+pub struct CancelHandle {
+    inner: Arc<CancelState>,
+}
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        let waker = self
+            .inner
+            .waker
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn register(&self, waker: &Waker) {
+        let mut slot = self
+            .inner
+            .waker
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        match slot.as_ref() {
+            Some(current) if current.will_wake(waker) => {}
+            _ => *slot = Some(waker.clone()),
+        }
+    }
+}
+
+impl fmt::Debug for CancelHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelHandle")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// The body of `resp` as it arrives from the network.
+pub(crate) fn body_chunks(
+    mut resp: reqwest::Response,
+) -> impl Stream<Item = Result<impl AsRef<[u8]>, Error>> {
+    try_stream! {
+        while let Some(bytes) = resp.chunk().await? {
+            yield bytes;
+        }
+    }
+}
+
+/// Decodes the JSON data of the server-sent events in `body`, up to the `[DONE]` event.
+pub(crate) fn json_events<T: DeserializeOwned>(
+    body: impl Stream<Item = Result<impl AsRef<[u8]>, Error>>,
+) -> impl Stream<Item = Result<T, Error>> {
+    try_stream! {
+        let mut body = pin!(body);
+        let mut decoder = SseDecoder::default();
+        'stream: while let Some(bytes) = next(&mut body).await {
+            for event in decoder.feed(bytes?.as_ref())? {
+                if event.data == "[DONE]" {
+                    break 'stream;
+                }
+                yield serde_json::from_str(&event.data)?;
+            }
+        }
+    }
+}
+
+/// A dispatched server-sent event.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct SseEvent {
+    /// The `event:` field, `None` for the default `message` type.
+    pub event: Option<String>,
+    /// The `data:` fields, joined by newlines.
+    pub data: String,
+    /// The last event ID seen so far.
+    pub id: Option<String>,
+}
+
+/// An incremental decoder for [server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation).
+///
+/// Network chunks may end anywhere, including in the middle of a line or of a multibyte
+/// character, so incomplete lines are buffered until the rest arrives.
+#[derive(Debug, Default)]
+pub(crate) struct SseDecoder {
+    buf: Vec<u8>,
+    /// Whether the previous chunk ended with `\r`, so a leading `\n` belongs to that line break.
+    pending_cr: bool,
+    started: bool,
+    event: Option<String>,
+    /// The `data:` fields of the current event, each followed by a newline.
+    data: String,
+    id: Option<String>,
+}
+
+impl SseDecoder {
+    /// Feeds the next chunk of the stream and returns the events completed by it.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<SseEvent>, Error> {
+        let mut chunk = chunk;
+        if self.pending_cr && !chunk.is_empty() {
+            self.pending_cr = false;
+            if chunk[0] == b'\n' {
+                chunk = &chunk[1..];
+            }
+        }
+        self.buf.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        let mut start = 0;
+        while let Some(offset) = self.buf[start..]
+            .iter()
+            .position(|&b| b == b'\n' || b == b'\r')
+        {
+            let end = start + offset;
+            let line_ending = if self.buf[end] == b'\r' {
+                match self.buf.get(end + 1) {
+                    Some(b'\n') => 2,
+                    Some(_) => 1,
+                    None => {
+                        self.pending_cr = true;
+                        1
+                    }
+                }
+            } else {
+                1
+            };
+
+            let line = std::str::from_utf8(&self.buf[start..end])
+                .map_err(|err| Error::Protocol(format!("SSE line is not valid UTF-8: {err}")))?
+                .to_string();
+            if let Some(event) = self.process_line(&line) {
+                events.push(event);
+            }
+            start = end + line_ending;
+        }
+        self.buf.drain(..start);
+
+        Ok(events)
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        let line = if self.started {
+            line
+        } else {
+            self.started = true;
+            line.strip_prefix('\u{feff}').unwrap_or(line)
+        };
+
+        if line.is_empty() {
+            // The event type is reset even if nothing is dispatched.
+            let event = self.event.take();
+            let mut data = std::mem::take(&mut self.data);
+            if data.is_empty() {
+                return None;
+            }
+            data.pop();
+            return Some(SseEvent {
+                event,
+                data,
+                id: self.id.clone(),
+            });
+        }
+
+        // Lines starting with a colon are comments, e.g. `: keep-alive`.
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "event" => self.event = Some(value.to_string()),
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            // `retry` only matters for reconnecting, which this client does not do.
+            _ => {}
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        CancelPolicy, ChatOptions, Client, FinishReason,
+        api::response::streaming::{self, Chunk},
+        message::Message,
+        mock::{MockServer, stalled_answer},
+    };
+
+    /// A `deepseek-reasoner` stream recorded from the API, with keep-alive comments and the usage
+    /// requested via `stream_options`.
+    const RECORDED: &str = concat!(
+        ": keep-alive\n\n",
+        r#"data: {"id":"0f6c5e1a-8d3b-4c1e-9a62-5b1d7f3e2c48","object":"chat.completion.chunk","created":1745812345,"model":"deepseek-reasoner","system_fingerprint":"fp_5417b77867_prod0425fp8","choices":[{"index":0,"delta":{"role":"assistant","content":null,"reasoning_content":""},"logprobs":null,"finish_reason":null}]}"#,
+        "\n\n",
+        r#"data: {"id":"0f6c5e1a-8d3b-4c1e-9a62-5b1d7f3e2c48","object":"chat.completion.chunk","created":1745812345,"model":"deepseek-reasoner","system_fingerprint":"fp_5417b77867_prod0425fp8","choices":[{"index":0,"delta":{"content":null,"reasoning_content":"嗯，用户"},"logprobs":null,"finish_reason":null}]}"#,
+        "\n\n",
+        ": keep-alive\n\n",
+        r#"data: {"id":"0f6c5e1a-8d3b-4c1e-9a62-5b1d7f3e2c48","object":"chat.completion.chunk","created":1745812345,"model":"deepseek-reasoner","system_fingerprint":"fp_5417b77867_prod0425fp8","choices":[{"index":0,"delta":{"content":null,"reasoning_content":"在打招呼。"},"logprobs":null,"finish_reason":null}]}"#,
+        "\n\n",
+        r#"data: {"id":"0f6c5e1a-8d3b-4c1e-9a62-5b1d7f3e2c48","object":"chat.completion.chunk","created":1745812345,"model":"deepseek-reasoner","system_fingerprint":"fp_5417b77867_prod0425fp8","choices":[{"index":0,"delta":{"content":"你好！👋","reasoning_content":null},"logprobs":null,"finish_reason":null}]}"#,
+        "\n\n",
+        r#"data: {"id":"0f6c5e1a-8d3b-4c1e-9a62-5b1d7f3e2c48","object":"chat.completion.chunk","created":1745812345,"model":"deepseek-reasoner","system_fingerprint":"fp_5417b77867_prod0425fp8","choices":[{"index":0,"delta":{"content":"","reasoning_content":null},"logprobs":null,"finish_reason":"stop"}],"usage":{"prompt_tokens":13,"completion_tokens":25,"total_tokens":38,"prompt_tokens_details":{"cached_tokens":0},"completion_tokens_details":{"reasoning_tokens":19},"prompt_cache_hit_tokens":0,"prompt_cache_miss_tokens":13}}"#,
+        "\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    /// A `deepseek-chat` stream recorded from the API, in which the model calls a tool.
+    const RECORDED_TOOL_CALL: &str = concat!(
+        r#"data: {"id":"7a2e9c4b-1f38-4d6a-b0e5-3c8f2d9a6e17","object":"chat.completion.chunk","created":1745812401,"model":"deepseek-chat","system_fingerprint":"fp_8802369eaa_prod0425fp8","choices":[{"index":0,"delta":{"role":"assistant","content":""},"logprobs":null,"finish_reason":null}]}"#,
+        "\n\n",
+        r#"data: {"id":"7a2e9c4b-1f38-4d6a-b0e5-3c8f2d9a6e17","object":"chat.completion.chunk","created":1745812401,"model":"deepseek-chat","system_fingerprint":"fp_8802369eaa_prod0425fp8","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_0_5b3e8f1c-2a4d-4e6b-9c7a-1d0f3e5b7a92","type":"function","function":{"name":"get_weather","arguments":""}}]},"logprobs":null,"finish_reason":null}]}"#,
+        "\n\n",
+        r#"data: {"id":"7a2e9c4b-1f38-4d6a-b0e5-3c8f2d9a6e17","object":"chat.completion.chunk","created":1745812401,"model":"deepseek-chat","system_fingerprint":"fp_8802369eaa_prod0425fp8","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"location\":"}}]},"logprobs":null,"finish_reason":null}]}"#,
+        "\n\n",
+        r#"data: {"id":"7a2e9c4b-1f38-4d6a-b0e5-3c8f2d9a6e17","object":"chat.completion.chunk","created":1745812401,"model":"deepseek-chat","system_fingerprint":"fp_8802369eaa_prod0425fp8","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":" \"Hangzhou\"}"}}]},"logprobs":null,"finish_reason":null}]}"#,
+        "\n\n",
+        r#"data: {"id":"7a2e9c4b-1f38-4d6a-b0e5-3c8f2d9a6e17","object":"chat.completion.chunk","created":1745812401,"model":"deepseek-chat","system_fingerprint":"fp_8802369eaa_prod0425fp8","choices":[{"index":0,"delta":{"content":""},"logprobs":null,"finish_reason":"tool_calls"}],"usage":{"prompt_tokens":172,"completion_tokens":21,"total_tokens":193,"prompt_tokens_details":{"cached_tokens":128},"prompt_cache_hit_tokens":128,"prompt_cache_miss_tokens":44}}"#,
+        "\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    /// Decodes `recorded` with [`json_events`], delivered in network chunks of `size` bytes.
+    async fn decode_chunks(recorded: &str, size: usize) -> Vec<Chunk> {
+        let body = futures_util::stream::iter(recorded.as_bytes().chunks(size).map(Ok::<_, Error>));
+        let mut chunks = pin!(json_events::<Chunk>(body));
+        let mut decoded = Vec::new();
+        while let Some(chunk) = next(&mut chunks).await {
+            decoded.push(chunk.unwrap());
+        }
+        decoded
+    }
+
+    fn decode_all(chunks: &[&[u8]]) -> Vec<SseEvent> {
+        let mut decoder = SseDecoder::default();
+        chunks
+            .iter()
+            .flat_map(|chunk| decoder.feed(chunk).unwrap())
+            .collect()
+    }
+
+    fn data(events: &[SseEvent]) -> Vec<&str> {
+        events.iter().map(|event| event.data.as_str()).collect()
+    }
+
+    #[test]
+    fn recorded_stream_skips_comments() {
+        let events = decode_all(&[RECORDED.as_bytes()]);
+        assert_eq!(events.len(), 6);
+        assert!(events[1].data.contains("嗯，用户"));
+        assert_eq!(events[5].data, "[DONE]");
+    }
+
+    #[tokio::test]
+    async fn recorded_stream_decodes_into_chunks() {
+        for size in [1, 7, RECORDED.len()] {
+            let chunks = decode_chunks(RECORDED, size).await;
+            assert_eq!(chunks.len(), 5, "chunks of {size} bytes");
+
+            let mut reasoning = String::new();
+            let mut content = String::new();
+            for chunk in &chunks {
+                assert_eq!(chunk.model, crate::Model::DeepSeekReasoner);
+                let streaming::Delta::Assistant {
+                    content: text,
+                    reasoning_content,
+                    ..
+                } = &chunk.choices[0].delta
+                else {
+                    panic!("expected an assistant delta");
+                };
+                reasoning.extend(reasoning_content.as_deref());
+                content.extend(text.as_deref());
+            }
+            assert_eq!(reasoning, "嗯，用户在打招呼。");
+            assert_eq!(content, "你好！👋");
+
+            let last = chunks.last().unwrap();
+            assert_eq!(last.choices[0].finish_reason, Some(FinishReason::Stop));
+            let usage = last.usage.as_ref().unwrap();
+            assert_eq!(usage.total_tokens, 38);
+            assert_eq!(usage.reasoning_tokens(), 19);
+            assert!(chunks[..4].iter().all(|chunk| chunk.usage.is_none()));
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_tool_call_stream_decodes_into_chunks() {
+        let chunks = decode_chunks(RECORDED_TOOL_CALL, 11).await;
+        assert_eq!(chunks.len(), 5);
+
+        let deltas = chunks[1..4]
+            .iter()
+            .map(|chunk| match &chunk.choices[0].delta {
+                streaming::Delta::ToolCall { tool_calls } => &tool_calls[0],
+                delta => panic!("expected a tool call delta, got {delta:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            deltas[0].id.as_deref(),
+            Some("call_0_5b3e8f1c-2a4d-4e6b-9c7a-1d0f3e5b7a92")
+        );
+        assert_eq!(deltas[0].function.name.as_deref(), Some("get_weather"));
+        let arguments = deltas
+            .iter()
+            .map(|delta| delta.function.arguments.as_str())
+            .collect::<String>();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&arguments).unwrap(),
+            serde_json::json!({ "location": "Hangzhou" })
+        );
+
+        let last = chunks.last().unwrap();
+        assert_eq!(last.choices[0].finish_reason, Some(FinishReason::ToolCalls));
+        assert_eq!(last.usage.as_ref().unwrap().prompt_cache_hit_tokens, 128);
+    }
+
+    #[test]
+    fn recorded_stream_split_at_every_byte_decodes_identically() {
+        let bytes = RECORDED.as_bytes();
+        let expected = decode_all(&[bytes]);
+
+        for i in 0..=bytes.len() {
+            assert_eq!(
+                decode_all(&[&bytes[..i], &bytes[i..]]),
+                expected,
+                "split at {i}"
+            );
+        }
+
+        let one_byte_chunks = bytes.chunks(1).collect::<Vec<_>>();
+        assert_eq!(decode_all(&one_byte_chunks), expected);
+    }
+
+    #[test]
+    fn crlf_and_cr_line_endings() {
+        let expected = vec!["a", "b"];
+        assert_eq!(
+            data(&decode_all(&[b"data: a\r\n\r\ndata: b\r\n\r\n"])),
+            expected
+        );
+        assert_eq!(data(&decode_all(&[b"data: a\r\rdata: b\r\r"])), expected);
+        assert_eq!(
+            data(&decode_all(&[b"data: a\r", b"\n\r", b"\ndata: b\r\n\r\n"])),
+            expected
+        );
+    }
+
+    #[test]
+    fn fields_are_parsed() {
+        let events = decode_all(&[b"event: error\nid: 7\ndata: first\ndata:second\nretry: 10\n\n"]);
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                event: Some("error".to_string()),
+                data: "first\nsecond".to_string(),
+                id: Some("7".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn events_without_data_are_not_dispatched() {
+        let events = decode_all(&[b"event: ping\n\ndata: a\n\nevent: ping\nid: 1\n\ndata: b\n\n"]);
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: None,
+                    data: "a".to_string(),
+                    id: None,
+                },
+                SseEvent {
+                    event: None,
+                    data: "b".to_string(),
+                    id: Some("1".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_data_lines_are_kept() {
+        assert_eq!(data(&decode_all(&[b"data\n\n"])), vec![""]);
+        assert_eq!(data(&decode_all(&[b"data:\ndata\n\n"])), vec!["\n"]);
+        assert_eq!(data(&decode_all(&[b"data: a\ndata:\n\n"])), vec!["a\n"]);
+    }
+
+    #[test]
+    fn incomplete_event_is_not_dispatched() {
+        assert!(decode_all(&[b"data: partial"]).is_empty());
+        assert!(decode_all(&[b"data: partial\n"]).is_empty());
+    }
+
+    #[test]
+    fn invalid_utf8_is_an_error() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.feed(b"data: \xff\n\n").is_err());
+    }
+
+    /// Starts a chat on a server that stalls after answering `Hel`, and returns the stream once
+    /// that content has been received.
+    async fn stream_until_content<'a>(
+        client: &'a mut Client,
+        on_cancel: CancelPolicy,
+    ) -> ChatStream<'a> {
+        let options = ChatOptions {
+            on_cancel: Some(on_cancel),
+            ..ChatOptions::default()
+        };
+        let mut stream = client.chat_stream("Hi", options);
+        loop {
+            match next(&mut stream).await {
+                Some(Ok(Delta::Content { content, .. })) => {
+                    assert_eq!(content, "Hel");
+                    return stream;
+                }
+                Some(Ok(_)) => {}
+                other => panic!("expected content, got {other:?}"),
+            }
+        }
+    }
+
+    fn assert_partial_committed(context: &[Message]) {
+        let [Message::User(user), Message::Assistant(assistant)] = context else {
+            panic!("expected the user message and the partial answer, got {context:?}");
+        };
+        assert_eq!(user.content, "Hi");
+        assert_eq!(assistant.content, "Hel");
+        assert!(assistant.truncated);
+    }
+
+    #[tokio::test]
+    async fn dropping_the_stream_applies_the_cancel_policy() {
+        let server = MockServer::start(vec![stalled_answer("Hel"), stalled_answer("Hel")]).await;
+        let mut client = server.client();
+
+        drop(stream_until_content(&mut client, CancelPolicy::Rollback).await);
+        assert!(client.context.is_empty());
+
+        drop(stream_until_content(&mut client, CancelPolicy::CommitPartial).await);
+        assert_partial_committed(&client.context);
+    }
+
+    #[tokio::test]
+    async fn cancel_applies_the_cancel_policy() {
+        let server = MockServer::start(vec![stalled_answer("Hel"), stalled_answer("Hel")]).await;
+        let mut client = server.client();
+
+        let mut stream = stream_until_content(&mut client, CancelPolicy::Rollback).await;
+        stream.cancel();
+        assert!(next(&mut stream).await.is_none());
+        drop(stream);
+        assert!(client.context.is_empty());
+
+        let mut stream = stream_until_content(&mut client, CancelPolicy::CommitPartial).await;
+        stream.cancel();
+        assert!(next(&mut stream).await.is_none());
+        drop(stream);
+        assert_partial_committed(&client.context);
+    }
+
+    #[tokio::test]
+    async fn cancel_handle_wakes_the_stream_from_another_task() {
+        let server = MockServer::start(vec![stalled_answer("Hel"), stalled_answer("Hel")]).await;
+        let mut client = server.client();
+
+        for on_cancel in [CancelPolicy::Rollback, CancelPolicy::CommitPartial] {
+            let mut stream = stream_until_content(&mut client, on_cancel).await;
+            let handle = stream.cancel_handle();
+            let canceller = tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                handle.cancel();
+            });
+
+            // The server never sends the rest, so only the cancellation can end the stream.
+            let end = tokio::time::timeout(std::time::Duration::from_secs(5), next(&mut stream));
+            assert!(end.await.expect("cancellation woke the stream").is_none());
+            drop(stream);
+            canceller.await.unwrap();
+
+            match on_cancel {
+                CancelPolicy::Rollback => assert!(client.context.is_empty()),
+                CancelPolicy::CommitPartial => assert_partial_committed(&client.context),
+            }
+        }
+    }
 }
@@ -1,17 +1,84 @@
-use std::{future::Future, pin::Pin};
+use std::{fmt, future::Future, pin::Pin};
 
 use schemars::Schema;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
 
-use crate::api;
+use crate::{Error, api};
 
-pub type ToolFuture = Pin<Box<dyn Future<Output = String> + Send + 'static>>;
+/// The future returned by a tool's call function.
+///
+/// It fails if the arguments generated by the model do not match the tool's parameters.
+pub type ToolFuture =
+    Pin<Box<dyn Future<Output = Result<String, ArgumentsError>> + Send + 'static>>;
+
+/// The arguments generated by the model do not match the parameters of a tool.
+#[derive(Debug)]
+pub struct ArgumentsError {
+    /// The path of the offending value, e.g. `event.attendees[2]`, or `.` for the arguments as a
+    /// whole.
+    pub path: String,
+    pub source: serde_json::Error,
+}
+
+impl fmt::Display for ArgumentsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.as_str() {
+            "." => self.source.fmt(f),
+            path => write!(f, "`{path}`: {}", self.source),
+        }
+    }
+}
+
+impl std::error::Error for ArgumentsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Parses the arguments of a tool call, keeping track of where parsing failed.
+#[doc(hidden)]
+pub fn parse_arguments<T: DeserializeOwned>(args: &str) -> Result<T, ArgumentsError> {
+    let mut deserializer = serde_json::Deserializer::from_str(args);
+    let args =
+        serde_path_to_error::deserialize(&mut deserializer).map_err(|err| ArgumentsError {
+            path: err.path().to_string(),
+            source: err.into_inner(),
+        })?;
+    deserializer.end().map_err(|source| ArgumentsError {
+        path: ".".to_string(),
+        source,
+    })?;
+    Ok(args)
+}
+
+/// Serializes the result of a tool call. A result that cannot be serialized is reported to the
+/// model instead.
+#[doc(hidden)]
+pub fn serialize_result<T: Serialize>(result: &T) -> String {
+    serde_json::to_string(result)
+        .unwrap_or_else(|err| format!("The tool result could not be serialized: {err}"))
+}
+
+/// Prepares the schema generated for the parameters of a tool, converting it for strict mode if
+/// `strict` is set.
+#[doc(hidden)]
+pub fn parameters_schema(mut schema: Schema, strict: bool) -> Result<Schema, String> {
+    if strict {
+        schema = strict_schema(&schema)?;
+    }
+    Ok(schema)
+}
 
 #[derive(Clone, Copy)]
 pub struct Tool {
     pub(crate) name: &'static str,
     pub(crate) description: &'static str,
-    pub(crate) parameters: fn() -> &'static Schema,
+    /// The schema of the parameters, or why it could not be converted for strict mode.
+    pub(crate) parameters: fn() -> &'static Result<Schema, String>,
     pub(crate) call: fn(String) -> ToolFuture,
+    /// Whether the model must adhere to the schema, see `#[tool(strict)]`.
+    pub(crate) strict: bool,
 }
 
 impl Tool {
@@ -20,30 +87,132 @@ impl Tool {
     pub const fn new(
         name: &'static str,
         description: &'static str,
-        parameters: fn() -> &'static Schema,
+        parameters: fn() -> &'static Result<Schema, String>,
         call: fn(String) -> ToolFuture,
+        strict: bool,
     ) -> Self {
         Self {
             name,
             description,
             parameters,
             call,
+            strict,
         }
     }
+
+    /// The schema of the parameters as sent to the API.
+    ///
+    /// Fails if the tool is strict and its schema uses features strict mode does not support.
+    pub(crate) fn schema(&self) -> Result<&'static Schema, Error> {
+        (self.parameters)()
+            .as_ref()
+            .map_err(|reason| Error::InvalidParameter {
+                name: "tools",
+                reason: format!("tool `{}` cannot be strict: {reason}", self.name),
+            })
+    }
 }
 
-impl From<&Tool> for api::request::Tool {
-    fn from(value: &Tool) -> Self {
-        Self::Function {
+impl TryFrom<&Tool> for api::request::Tool {
+    type Error = Error;
+
+    fn try_from(value: &Tool) -> Result<Self, Error> {
+        Ok(Self::Function {
             name: value.name,
             description: value.description,
-            parameters: (value.parameters)(),
+            parameters: value.schema()?,
+            strict: value.strict,
+        })
+    }
+}
+
+/// The types supported by strict mode, see the
+/// [documentation](https://api-docs.deepseek.com/guides/function_calling#strict-mode-beta).
+const STRICT_TYPES: [&str; 6] = ["object", "string", "number", "integer", "boolean", "array"];
+
+/// Converts a schema generated by `schemars` into one accepted by strict mode: every object
+/// lists all of its properties as required and disallows additional ones.
+fn strict_schema(schema: &Schema) -> Result<Schema, String> {
+    let mut value = schema.as_value().clone();
+    if let Some(root) = value.as_object_mut() {
+        root.remove("$schema");
+        root.remove("title");
+    }
+    make_strict(&mut value)?;
+    Schema::try_from(value).map_err(|err| err.to_string())
+}
+
+fn make_strict(value: &mut Value) -> Result<(), String> {
+    let Some(schema) = value.as_object_mut() else {
+        // `true` and `false` schemas accept anything or nothing.
+        return Err("schemas must be objects".to_string());
+    };
+
+    for keyword in ["oneOf", "allOf", "not", "if", "patternProperties"] {
+        if schema.contains_key(keyword) {
+            return Err(format!("`{keyword}` is not supported"));
+        }
+    }
+
+    match schema.get("type") {
+        Some(Value::String(ty)) if STRICT_TYPES.contains(&ty.as_str()) => {}
+        Some(Value::String(ty)) => return Err(format!("type `{ty}` is not supported")),
+        Some(Value::Array(_)) => {
+            return Err("optional and nullable values are not supported".to_string());
         }
+        Some(_) => return Err("`type` must be a string".to_string()),
+        None => {}
     }
+
+    // `schemars` describes the width of numbers with formats such as `int32`, which strict mode
+    // rejects.
+    if matches!(
+        schema.get("type").and_then(Value::as_str),
+        Some("integer" | "number")
+    ) {
+        schema.remove("format");
+    }
+
+    if schema.get("type").and_then(Value::as_str) == Some("object") {
+        match schema.get("additionalProperties") {
+            None | Some(Value::Bool(false)) => {}
+            Some(_) => return Err("maps with arbitrary keys are not supported".to_string()),
+        }
+        schema.insert("additionalProperties".to_string(), Value::Bool(false));
+
+        let properties = schema
+            .entry("properties")
+            .or_insert_with(|| Value::Object(Map::new()));
+        let required = properties
+            .as_object()
+            .map(|properties| properties.keys().cloned().map(Value::String).collect())
+            .unwrap_or_default();
+        schema.insert("required".to_string(), Value::Array(required));
+    }
+
+    for keyword in ["properties", "$defs"] {
+        if let Some(Value::Object(schemas)) = schema.get_mut(keyword) {
+            for schema in schemas.values_mut() {
+                make_strict(schema)?;
+            }
+        }
+    }
+    if let Some(items) = schema.get_mut("items") {
+        make_strict(items)?;
+    }
+    if let Some(Value::Array(schemas)) = schema.get_mut("anyOf") {
+        for schema in schemas {
+            make_strict(schema)?;
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use serde_json::json;
+
     use crate::tool;
 
     #[tool]
@@ -73,7 +242,7 @@ mod tests {
 
     #[tokio::test]
     async fn call_invokes_original_function() {
-        let result = (ADD.call)(r#"{"a":3,"b":4}"#.to_string()).await;
+        let result = (ADD.call)(r#"{"a":3,"b":4}"#.to_string()).await.unwrap();
         assert_eq!(result, "7");
     }
 
@@ -85,10 +254,34 @@ mod tests {
     #[tokio::test]
     async fn no_args_function_accepts_empty_object() {
         assert_eq!(NO_ARGS.name, "no_args");
-        let result = (NO_ARGS.call)("{}".to_string()).await;
+        let result = (NO_ARGS.call)("{}".to_string()).await.unwrap();
         assert_eq!(result, "42");
     }
 
+    #[tokio::test]
+    async fn call_rejects_malformed_arguments() {
+        let err = (ADD.call)(r#"{"a":"three","b":4}"#.to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(err.path, "a");
+        assert!(
+            err.to_string()
+                .starts_with("`a`: invalid type: string \"three\", expected i32")
+        );
+
+        let err = (ADD.call)(r#"{"a":1,"b":2} trailing"#.to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(err.path, ".");
+    }
+
+    #[tokio::test]
+    async fn nested_argument_errors_report_their_path() {
+        let args = r#"{"event":{"title":"Standup","attendees":["Ann",7]},"days_from_now":1}"#;
+        let err = (SCHEDULE.call)(args.to_string()).await.unwrap_err();
+        assert_eq!(err.path, "event.attendees[1]");
+    }
+
     #[tokio::test]
     async fn original_function_is_still_callable() {
         assert_eq!(add(1, 2).await, 3);
@@ -96,7 +289,7 @@ mod tests {
 
     #[test]
     fn parameters_schema_lists_fields() {
-        let schema = serde_json::to_value((ADD.parameters)()).unwrap();
+        let schema = serde_json::to_value(ADD.schema().unwrap()).unwrap();
         let props = schema.get("properties").expect("schema has properties");
         assert!(props.get("a").is_some());
         assert!(props.get("b").is_some());
@@ -108,4 +301,49 @@ mod tests {
         let b = ADD;
         assert_eq!(a.name, b.name);
     }
+
+    #[derive(serde::Deserialize, schemars::JsonSchema)]
+    struct Event {
+        title: String,
+        attendees: Vec<String>,
+    }
+
+    #[tool(strict)]
+    /// Schedules an event.
+    async fn schedule(event: Event, days_from_now: u32) -> bool {
+        !event.title.is_empty() && !event.attendees.is_empty() && days_from_now < 365
+    }
+
+    #[derive(serde::Deserialize, schemars::JsonSchema)]
+    struct Filter {
+        tag: Option<String>,
+    }
+
+    #[tool(strict)]
+    /// Searches the notes.
+    async fn search(query: String, filter: Filter) -> bool {
+        filter.tag.is_some_and(|tag| query.contains(&tag))
+    }
+
+    #[test]
+    fn strict_schema_requires_all_properties() {
+        const { assert!(SCHEDULE.strict) };
+        let schema = SCHEDULE.schema().unwrap();
+        let schema = schema.as_value();
+
+        assert_eq!(schema.get("$schema"), None);
+        assert_eq!(schema["additionalProperties"], json!(false));
+        assert_eq!(schema["required"], json!(["days_from_now", "event"]));
+        assert_eq!(schema["properties"]["days_from_now"].get("format"), None);
+
+        let event = &schema["$defs"]["Event"];
+        assert_eq!(event["additionalProperties"], json!(false));
+        assert_eq!(event["required"], json!(["attendees", "title"]));
+    }
+
+    #[test]
+    fn strict_schema_rejects_optional_fields() {
+        assert!(SEARCH.schema().is_err());
+        assert!(ADD.schema().is_ok());
+    }
 }
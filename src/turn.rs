@@ -0,0 +1,236 @@
+use crate::{
+    CancelPolicy, Client,
+    message::{self, Message},
+};
+
+/// A user turn in progress.
+///
+/// If it is dropped before [`Turn::finish`] is called, e.g. because a stream was dropped or a
+/// request failed, the context is restored to a consistent state according to its
+/// [`CancelPolicy`].
+pub(crate) struct Turn<'a> {
+    pub client: &'a mut Client,
+    /// The assistant message that is currently being streamed.
+    pub partial: Option<message::Assistant>,
+    /// The index of the user message in the context.
+    start: usize,
+    on_cancel: CancelPolicy,
+    finished: bool,
+}
+
+impl<'a> Turn<'a> {
+    /// Pushes the user message to the context and starts the turn.
+    pub fn begin(client: &'a mut Client, message: &str, on_cancel: CancelPolicy) -> Self {
+        let start = client.context.len();
+        client.context.push(
+            message::User {
+                name: None,
+                content: message.to_string(),
+            }
+            .into(),
+        );
+
+        Self {
+            client,
+            partial: None,
+            start,
+            on_cancel,
+            finished: false,
+        }
+    }
+
+    /// Makes the model continue `prefix` instead of starting a new answer.
+    pub fn prefill(&mut self, prefix: String) {
+        self.partial = Some(message::Assistant {
+            name: None,
+            content: prefix,
+            reasoning_content: None,
+            tool_calls: None,
+            truncated: false,
+        });
+    }
+
+    /// The messages added to the context after the user message.
+    pub fn transcript(&self) -> Vec<Message> {
+        self.client.context[self.start + 1..].to_vec()
+    }
+
+    /// Marks the turn as complete and returns its [transcript](Self::transcript).
+    pub fn finish(mut self) -> Vec<Message> {
+        self.finished = true;
+        self.transcript()
+    }
+
+    fn commit_partial(&mut self) {
+        let has_answer = self.partial.as_ref().is_some_and(|partial| {
+            !partial.content.is_empty() || partial.reasoning_content.is_some()
+        });
+        if !has_answer && self.client.context.len() == self.start + 1 {
+            // Nothing was received, so committing would leave the user message without a reply.
+            self.client.context.truncate(self.start);
+            return;
+        }
+
+        let context = &mut self.client.context;
+
+        // The API rejects a context with unanswered tool calls, so answer them.
+        let unanswered = context[self.start..]
+            .iter()
+            .rev()
+            .find_map(|msg| match msg {
+                Message::Assistant(assistant) => assistant.tool_calls.clone(),
+                _ => None,
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|tool_call| {
+                !context[self.start..].iter().any(
+                    |msg| matches!(msg, Message::Tool(tool) if tool.tool_call_id == tool_call.id),
+                )
+            })
+            .collect::<Vec<_>>();
+        for tool_call in unanswered {
+            context.push(
+                message::Tool {
+                    tool_call_id: tool_call.id,
+                    content: "The tool call was cancelled.".to_string(),
+                }
+                .into(),
+            );
+        }
+
+        if let Some(mut partial) = self.partial.take()
+            && (!partial.content.is_empty() || partial.reasoning_content.is_some())
+        {
+            // The arguments of tool calls may be cut off, so only the text is kept.
+            partial.tool_calls = None;
+            partial.truncated = true;
+            context.push(partial.into());
+        }
+    }
+}
+
+impl Drop for Turn<'_> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        match self.on_cancel {
+            CancelPolicy::Rollback => self.client.context.truncate(self.start),
+            CancelPolicy::CommitPartial => self.commit_partial(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Model;
+
+    fn client() -> Client {
+        let mut client = Client::new(Model::DeepSeekChat, "sk-test");
+        client.context.push(Message::system("Be brief."));
+        client
+    }
+
+    fn tool_call(id: &str) -> message::ToolCall {
+        message::ToolCall {
+            id: id.to_string(),
+            function: message::Function {
+                name: "add".to_string(),
+                arguments: "{}".to_string(),
+            },
+        }
+    }
+
+    fn assistant(content: &str, tool_calls: Option<Vec<message::ToolCall>>) -> message::Assistant {
+        message::Assistant {
+            name: None,
+            content: content.to_string(),
+            reasoning_content: None,
+            tool_calls,
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn finished_turn_is_kept() {
+        let mut client = client();
+        let turn = Turn::begin(&mut client, "Hi", CancelPolicy::Rollback);
+        turn.client.context.push(assistant("Hello", None).into());
+        assert_eq!(turn.finish().len(), 1);
+        assert_eq!(client.context.len(), 3);
+    }
+
+    #[test]
+    fn rollback_removes_the_whole_turn() {
+        let mut client = client();
+        let mut turn = Turn::begin(&mut client, "Hi", CancelPolicy::Rollback);
+        turn.client
+            .context
+            .push(assistant("", Some(vec![tool_call("a")])).into());
+        turn.partial = Some(assistant("Hel", None));
+        drop(turn);
+
+        assert_eq!(client.context.len(), 1);
+    }
+
+    #[test]
+    fn commit_partial_answers_tool_calls_and_marks_truncation() {
+        let mut client = client();
+        let turn = Turn::begin(&mut client, "Hi", CancelPolicy::CommitPartial);
+        turn.client
+            .context
+            .push(assistant("", Some(vec![tool_call("a"), tool_call("b")])).into());
+        turn.client.context.push(
+            message::Tool {
+                tool_call_id: "a".to_string(),
+                content: "1".to_string(),
+            }
+            .into(),
+        );
+        drop(turn);
+
+        let roles = client.context.iter().map(Message::role).collect::<Vec<_>>();
+        use crate::Role::*;
+        assert_eq!(roles, vec![System, User, Assistant, Tool, Tool]);
+        assert!(matches!(&client.context[4], Message::Tool(tool) if tool.tool_call_id == "b"));
+
+        let mut turn = Turn::begin(&mut client, "Again", CancelPolicy::CommitPartial);
+        turn.partial = Some(assistant("Hel", Some(vec![tool_call("c")])));
+        drop(turn);
+
+        let Some(Message::Assistant(last)) = client.context.last() else {
+            panic!("expected the partial answer to be committed");
+        };
+        assert!(last.truncated);
+        assert_eq!(last.content, "Hel");
+        assert!(last.tool_calls.is_none());
+    }
+
+    #[test]
+    fn prefill_is_committed_as_the_partial_answer() {
+        let mut client = client();
+        let mut turn = Turn::begin(&mut client, "Write JSON", CancelPolicy::CommitPartial);
+        turn.prefill("{\"name\":".to_string());
+        drop(turn);
+
+        let Some(Message::Assistant(last)) = client.context.last() else {
+            panic!("expected the prefix to be committed");
+        };
+        assert_eq!(last.content, "{\"name\":");
+        assert!(last.truncated);
+    }
+
+    #[test]
+    fn commit_partial_rolls_back_when_nothing_was_received() {
+        let mut client = client();
+        let mut turn = Turn::begin(&mut client, "Hi", CancelPolicy::CommitPartial);
+        turn.partial = Some(assistant("", None));
+        drop(turn);
+
+        assert_eq!(client.context.len(), 1);
+        assert_eq!(client.context[0].role(), crate::Role::System);
+    }
+}